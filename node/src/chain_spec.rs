@@ -1,7 +1,8 @@
 use sp_core::{Pair, Public, sr25519};
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, AdminConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, AdminConfig, ChanceConfig,
+	PoolerConfig,
+	SudoConfig, SystemConfig, TokensConfig, WASM_BINARY, Signature, NATIVE_CURRENCY_ID, TEST_USD_CURRENCY_ID,
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -143,6 +144,10 @@ fn testnet_genesis(
 			// Configure endowed accounts with initial balance of 1 << 60.
 			balances: endowed_accounts.iter().cloned().map(|k|(k, 1 << 60)).collect(),
 		}),
+		orml_tokens: Some(TokensConfig {
+			// Endow the same accounts with a stablecoin-style test asset.
+			endowed_accounts: endowed_accounts.iter().cloned().map(|k| (k, TEST_USD_CURRENCY_ID, 1 << 60)).collect(),
+		}),
 		pallet_aura: Some(AuraConfig {
 			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
 		}),
@@ -153,8 +158,23 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: root_key.clone(),
 		}),
+		pallet_pooler: Some(PoolerConfig {
+			// The sudo account provides the initial liquidity so bets can be placed right away.
+			positions: vec![
+				(NATIVE_CURRENCY_ID, root_key.clone(), 1 << 56),
+				(TEST_USD_CURRENCY_ID, root_key.clone(), 1 << 56),
+			],
+			reserves: vec![],
+		}),
+		pallet_chance: Some(ChanceConfig {
+			parameters: vec![],
+			treasury: None,
+			paused: vec![],
+			oracle_key: Some(get_from_seed::<sr25519::Public>("Oracle")),
+		}),
 		pallet_admin: Some(AdminConfig {
 				settlers: vec![root_key],
-		})
+				..Default::default()
+		}),
 	}
 }
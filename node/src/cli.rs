@@ -1,5 +1,9 @@
 use structopt::StructOpt;
 use sc_cli::RunCmd;
+use crate::verify_bet::VerifyBetCmd;
+use crate::ocw_key::KeyCmd;
+use crate::service::Sealing;
+use crate::simulate::SimulateCmd;
 
 #[derive(Debug, StructOpt)]
 pub struct Cli {
@@ -8,6 +12,16 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// The secret URI of the `ocwc` key the offchain worker signs with, kept in memory only.
+	/// `--dev` uses `//Alice` unless this is set.
+	#[structopt(long)]
+	pub ocw_key_seed: Option<String>,
+
+	/// Seal blocks `instant`ly when a transaction arrives or `manual`ly through the
+	/// `engine_createBlock` RPC, instead of running Aura and GRANDPA.
+	#[structopt(long, possible_values = &["instant", "manual"])]
+	pub sealing: Option<Sealing>,
 }
 
 #[derive(Debug, StructOpt)]
@@ -36,4 +50,13 @@ pub enum Subcommand {
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Recompute the outcome of a provably-fair bet from its published randomness.
+	VerifyBet(VerifyBetCmd),
+
+	/// Manage the offchain worker's keys.
+	Key(KeyCmd),
+
+	/// Estimate the house edge and pool solvency with Monte Carlo simulations.
+	Simulate(SimulateCmd),
 }
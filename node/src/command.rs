@@ -17,6 +17,7 @@
 
 use crate::{chain_spec, service};
 use crate::cli::{Cli, Subcommand};
+use crate::ocw_key::KeyCmd;
 use sc_cli::{SubstrateCli, RuntimeVersion, Role, ChainSpec};
 use sc_service::PartialComponents;
 use node_template_runtime::Block;
@@ -124,11 +125,19 @@ pub fn run() -> sc_cli::Result<()> {
 				You can enable it with `--features runtime-benchmarks`.".into())
 			}
 		},
+		Some(Subcommand::VerifyBet(cmd)) => cmd.run(),
+		Some(Subcommand::Simulate(cmd)) => cmd.run(),
+		Some(Subcommand::Key(KeyCmd::InsertOcw(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| cmd.run(config.keystore))
+		},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let ocw_key_seed = cli.ocw_key_seed.clone();
+			let sealing = cli.sealing;
 			runner.run_node_until_exit(|config| match config.role {
 				Role::Light => service::new_light(config),
-				_ => service::new_full(config),
+				_ => service::new_full(config, ocw_key_seed, sealing),
 			})
 		}
 	}
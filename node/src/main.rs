@@ -7,6 +7,10 @@ mod service;
 mod cli;
 mod command;
 mod rpc;
+mod metrics;
+mod verify_bet;
+mod ocw_key;
+mod simulate;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
@@ -0,0 +1,151 @@
+//! Betting metrics exported on the node's Prometheus endpoint.
+//!
+//! On every new best block the settlement backlog, the settlements in that block and the pools'
+//! liquidity are read through `ChanceApi`. The offchain worker's oracle request statistics are
+//! read from the persistent offchain storage it records them in.
+
+use std::sync::Arc;
+use futures::StreamExt;
+use codec::Decode;
+use sc_client_api::{Backend, BlockchainEvents};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+use sp_runtime::generic::BlockId;
+use substrate_prometheus_endpoint::{register, F64, Gauge, GaugeVec, Opts, PrometheusError, Registry, U64};
+use pallet_chance_rpc::ChanceRuntimeApi;
+use node_template_runtime::{
+	opaque::Block, pallet_ocw::{FetchStats, FETCH_STATS_KEY}, AccountId, Balance, BlockNumber, CurrencyId, Hash,
+	NATIVE_CURRENCY_ID, TEST_USD_CURRENCY_ID,
+};
+
+/// The currencies whose pools are reported.
+const CURRENCIES: [(CurrencyId, &str); 2] = [(NATIVE_CURRENCY_ID, "native"), (TEST_USD_CURRENCY_ID, "test_usd")];
+
+/// The gauges updated on every new best block.
+#[derive(Clone)]
+pub struct BettingMetrics {
+	pending_bets: Gauge<U64>,
+	oldest_pending_bet_age: Gauge<U64>,
+	settlements: Gauge<U64>,
+	pool_reserves: GaugeVec<F64>,
+	pool_share_price: GaugeVec<F64>,
+	ocw_fetches: Gauge<U64>,
+	ocw_fetch_errors: Gauge<U64>,
+	ocw_fetch_latency: Gauge<U64>,
+}
+
+impl BettingMetrics {
+	/// Register the gauges with `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			pending_bets: register(Gauge::new(
+				"chance_pending_bets", "Number of bets waiting for a settler",
+			)?, registry)?,
+			oldest_pending_bet_age: register(Gauge::new(
+				"chance_oldest_pending_bet_age_blocks", "Blocks since the oldest pending bet was placed",
+			)?, registry)?,
+			settlements: register(Gauge::new(
+				"chance_settlements_per_block", "Number of bets settled in the best block",
+			)?, registry)?,
+			pool_reserves: register(GaugeVec::new(
+				Opts::new("pooler_reserves", "Funds held by the pool"),
+				&["currency"],
+			)?, registry)?,
+			pool_share_price: register(GaugeVec::new(
+				Opts::new("pooler_share_price", "Pool reserves per issued LP unit"),
+				&["currency"],
+			)?, registry)?,
+			ocw_fetches: register(Gauge::new(
+				"ocw_oracle_fetches", "Number of oracle requests made by the offchain worker",
+			)?, registry)?,
+			ocw_fetch_errors: register(Gauge::new(
+				"ocw_oracle_fetch_errors", "Number of oracle requests that failed",
+			)?, registry)?,
+			ocw_fetch_latency: register(Gauge::new(
+				"ocw_oracle_fetch_latency_ms", "Duration of the last oracle request in milliseconds",
+			)?, registry)?,
+		})
+	}
+
+	/// Update the gauges on every new best block until the client shuts down.
+	pub async fn run<C, B>(self, client: Arc<C>, backend: Arc<B>) where
+		C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block>,
+		C::Api: ChanceRuntimeApi<Block, AccountId, CurrencyId, Balance, BlockNumber, Hash>,
+		B: Backend<Block>,
+	{
+		let mut settled = None;
+		let mut notifications = client.import_notification_stream();
+		while let Some(notification) = notifications.next().await {
+			if !notification.is_new_best {
+				continue;
+			}
+			let at = BlockId::Hash(notification.hash);
+			let number = client.number(notification.hash).ok().flatten().unwrap_or_default();
+			if let Err(e) = self.update_chain(&*client, &at, number, &mut settled) {
+				log::debug!(target: "metrics", "Cannot read betting metrics at {}: {:?}", notification.hash, e);
+			}
+			self.update_ocw(&*backend);
+		}
+	}
+
+	fn update_chain<C>(
+		&self,
+		client: &C,
+		at: &BlockId<Block>,
+		number: BlockNumber,
+		settled: &mut Option<u64>,
+	) -> Result<(), sp_api::ApiErrorFor<C, Block>> where
+		C: ProvideRuntimeApi<Block>,
+		C::Api: ChanceRuntimeApi<Block, AccountId, CurrencyId, Balance, BlockNumber, Hash>,
+	{
+		let api = client.runtime_api();
+
+		let queue = api.settlement_queue(at)?;
+		self.pending_bets.set(queue.pending as u64);
+		self.oldest_pending_bet_age.set(queue.oldest_placed_at.map_or(0, |placed_at| number.saturating_sub(placed_at) as u64));
+
+		let mut total = 0u64;
+		for (currency_id, label) in CURRENCIES.iter() {
+			total = total.saturating_add(api.bet_totals(at, *currency_id)?.settled);
+			let pool = api.pool_status(at, *currency_id)?;
+			self.pool_reserves.with_label_values(&[label]).set(pool.reserves as f64);
+			let price = if pool.total_supply == 0 { 0.0 } else { pool.reserves as f64 / pool.total_supply as f64 };
+			self.pool_share_price.with_label_values(&[label]).set(price);
+		}
+		// The first block seen has no predecessor to compare against.
+		if let Some(previous) = settled.replace(total) {
+			self.settlements.set(total.saturating_sub(previous));
+		}
+		Ok(())
+	}
+
+	fn update_ocw<B: Backend<Block>>(&self, backend: &B) {
+		let stats = backend.offchain_storage()
+			.and_then(|storage| storage.get(STORAGE_PREFIX, FETCH_STATS_KEY))
+			.and_then(|encoded| FetchStats::decode(&mut &encoded[..]).ok());
+		if let Some(stats) = stats {
+			self.ocw_fetches.set(stats.fetches);
+			self.ocw_fetch_errors.set(stats.errors);
+			self.ocw_fetch_latency.set(stats.last_latency_ms);
+		}
+	}
+}
+
+/// Register the betting metrics and spawn the task updating them, if Prometheus is enabled.
+pub fn spawn<C, B>(
+	registry: Option<&Registry>,
+	task_manager: &sc_service::TaskManager,
+	client: Arc<C>,
+	backend: Arc<B>,
+) -> Result<(), PrometheusError> where
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + BlockchainEvents<Block> + Send + Sync + 'static,
+	C::Api: ChanceRuntimeApi<Block, AccountId, CurrencyId, Balance, BlockNumber, Hash>,
+	B: Backend<Block> + 'static,
+{
+	if let Some(registry) = registry {
+		let metrics = BettingMetrics::register(registry)?;
+		task_manager.spawn_handle().spawn("betting-metrics", metrics.run(client, backend));
+	}
+	Ok(())
+}
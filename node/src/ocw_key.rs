@@ -0,0 +1,61 @@
+//! The `key insert-ocw` subcommand, which stores the offchain worker's signing key.
+
+use structopt::StructOpt;
+use sc_cli::{CliConfiguration, KeystoreParams, SharedParams};
+use sc_service::config::KeystoreConfig;
+use sp_core::{crypto::Pair, sr25519};
+use node_template_runtime::pallet_ocw::KEY_TYPE;
+
+/// Key management for the offchain worker.
+#[derive(Debug, StructOpt)]
+pub enum KeyCmd {
+	/// Insert the `ocwc` key the offchain worker signs settlements with into the keystore.
+	InsertOcw(InsertOcwCmd),
+}
+
+/// Insert an sr25519 `ocwc` key derived from a secret URI.
+#[derive(Debug, StructOpt)]
+pub struct InsertOcwCmd {
+	/// The secret URI of the key, e.g. a mnemonic or `//Alice`. The account it derives must be
+	/// a settler for its settlements to be accepted.
+	#[structopt(long)]
+	pub suri: String,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub keystore_params: KeystoreParams,
+}
+
+impl InsertOcwCmd {
+	/// Store the key in the node's keystore and print its public key.
+	pub fn run(&self, keystore: KeystoreConfig) -> sc_cli::Result<()> {
+		let (path, password) = match keystore {
+			KeystoreConfig::Path { path, password } => (path, password),
+			KeystoreConfig::InMemory => {
+				return Err(sc_cli::Error::Input("Keys can only be inserted into a keystore on disk".into()));
+			},
+		};
+		let pair = sr25519::Pair::from_string(&self.suri, None)
+			.map_err(|e| sc_cli::Error::Input(format!("Invalid secret URI: {:?}", e)))?;
+		let store = sc_keystore::Store::open(path, password)
+			.map_err(|e| sc_cli::Error::Input(format!("Cannot open the keystore: {}", e)))?;
+		store.write().insert_unknown(KEY_TYPE, &self.suri, pair.public().as_ref())
+			.map_err(|_| sc_cli::Error::Input("Cannot write the key to the keystore".into()))?;
+		println!("Inserted ocwc key 0x{}", pair.public().as_ref().iter().map(|b| format!("{:02x}", b)).collect::<String>());
+		Ok(())
+	}
+}
+
+impl CliConfiguration for InsertOcwCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn keystore_params(&self) -> Option<&KeystoreParams> {
+		Some(&self.keystore_params)
+	}
+}
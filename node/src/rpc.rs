@@ -7,12 +7,14 @@
 
 use std::sync::Arc;
 
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, CurrencyId, Hash, Index};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::{Error as BlockChainError, HeaderMetadata, HeaderBackend};
 use sp_block_builder::BlockBuilder;
 pub use sc_rpc_api::DenyUnsafe;
 use sp_transaction_pool::TransactionPool;
+use sc_consensus_manual_seal::rpc::EngineCommand;
+use futures::channel::mpsc::Sender;
 
 
 /// Full client dependencies.
@@ -23,6 +25,8 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Where `engine_*` calls are sent when blocks are sealed manually.
+	pub command_sink: Option<Sender<EngineCommand<Hash>>>,
 }
 
 /// Instantiate all full RPC extensions.
@@ -34,17 +38,23 @@ pub fn create_full<C, P>(
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_admin_rpc::AdminRuntimeApi<Block, AccountId, BlockNumber>,
+	C::Api: pallet_chance_rpc::ChanceRuntimeApi<Block, AccountId, CurrencyId, Balance, BlockNumber, Hash>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+	use pallet_admin_rpc::{Admin, AdminApi};
+	use pallet_chance_rpc::{Chance, ChanceApi};
+	use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
 	let FullDeps {
 		client,
 		pool,
 		deny_unsafe,
+		command_sink,
 	} = deps;
 
 	io.extend_with(
@@ -55,6 +65,20 @@ pub fn create_full<C, P>(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
 
+	io.extend_with(
+		AdminApi::to_delegate(Admin::new(client.clone()))
+	);
+
+	io.extend_with(
+		ChanceApi::to_delegate(Chance::new(client.clone()))
+	);
+
+	if let Some(command_sink) = command_sink {
+		io.extend_with(
+			ManualSealApi::to_delegate(ManualSeal::new(command_sink))
+		);
+	}
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
@@ -1,15 +1,18 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use sc_client_api::{ExecutorProvider, RemoteBackend};
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
-use sp_inherents::InherentDataProviders;
+use sp_inherents::{InherentData, InherentDataProviders, InherentIdentifier, ProvideInherentData};
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
 use sp_consensus_aura::sr25519::{AuthorityPair as AuraPair};
 use sc_finality_grandpa::{FinalityProofProvider as GrandpaFinalityProofProvider, SharedVoterState};
+use sp_core::traits::BareCryptoStore;
+use sc_consensus_manual_seal::{InstantSealParams, ManualSealParams};
 
 // Our native executor instance.
 native_executor_instance!(
@@ -23,6 +26,64 @@ type FullClient = sc_service::TFullClient<Block, RuntimeApi, Executor>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 
+/// How a full node seals blocks instead of running Aura and GRANDPA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+	/// Seal a block as soon as a transaction enters the pool.
+	Instant,
+	/// Seal a block when asked through the `engine_createBlock` RPC.
+	Manual,
+}
+
+impl std::str::FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Sealing::Instant),
+			"manual" => Ok(Sealing::Manual),
+			_ => Err(format!("Unknown sealing `{}`, expected `instant` or `manual`", s)),
+		}
+	}
+}
+
+/// Provides timestamps one slot apart however quickly blocks are sealed, since the runtime
+/// rejects two blocks in the same Aura slot.
+struct SealingTimestampProvider(AtomicU64);
+
+impl SealingTimestampProvider {
+	fn new() -> Self {
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).expect("now is after the epoch; qed");
+		SealingTimestampProvider(AtomicU64::new(now.as_millis() as u64))
+	}
+}
+
+impl ProvideInherentData for SealingTimestampProvider {
+	fn inherent_identifier(&self) -> &'static InherentIdentifier {
+		&sp_timestamp::INHERENT_IDENTIFIER
+	}
+
+	fn provide_inherent_data(&self, inherent_data: &mut InherentData) -> Result<(), sp_inherents::Error> {
+		let slot_duration = node_template_runtime::SLOT_DURATION;
+		let timestamp = self.0.fetch_add(slot_duration, Ordering::SeqCst) + slot_duration;
+		inherent_data.put_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp)
+	}
+
+	fn error_to_string(&self, error: &[u8]) -> Option<String> {
+		sp_timestamp::InherentError::try_from(&sp_timestamp::INHERENT_IDENTIFIER, error).map(|e| format!("{:?}", e))
+	}
+}
+
+/// Insert the offchain worker's `ocwc` key derived from `seed` into the in-memory keystore.
+fn insert_ocw_key(keystore: &sc_keystore::KeyStorePtr, seed: Option<String>) -> Result<(), ServiceError> {
+	if let Some(seed) = seed {
+		keystore.write()
+			.sr25519_generate_new(node_template_runtime::pallet_ocw::KEY_TYPE, Some(&seed))
+			.map_err(|e| ServiceError::Other(format!("Failed to insert the ocwc key: {:?}", e)))?;
+	}
+	Ok(())
+}
+
 pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponents<
 	FullClient, FullBackend, FullSelectChain,
 	sp_consensus::DefaultImportQueue<Block, FullClient>,
@@ -80,13 +141,28 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
+///
+/// The offchain worker signs with an `ocwc` key derived from `ocw_key_seed`, or from the dev
+/// account seed when running `--dev`. Keys inserted this way only live in memory.
+///
+/// With `sealing` set, blocks are sealed on demand instead of by Aura and GRANDPA.
+pub fn new_full(
+	config: Configuration,
+	ocw_key_seed: Option<String>,
+	sealing: Option<Sealing>,
+) -> Result<TaskManager, ServiceError> {
+	if let Some(sealing) = sealing {
+		return new_full_sealing(config, ocw_key_seed, sealing);
+	}
+
 	let sc_service::PartialComponents {
 		client, backend, mut task_manager, import_queue, keystore, select_chain, transaction_pool,
 		inherent_data_providers,
 		other: (block_import, grandpa_link),
 	} = new_partial(&config)?;
 
+	insert_ocw_key(&keystore, ocw_key_seed.or_else(|| config.dev_key_seed.clone()))?;
+
 	let finality_proof_provider =
 		GrandpaFinalityProofProvider::new_for_service(backend.clone(), client.clone());
 
@@ -125,12 +201,16 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 				client: client.clone(),
 				pool: pool.clone(),
 				deny_unsafe,
+				command_sink: None,
 			};
 
 			crate::rpc::create_full(deps)
 		})
 	};
 
+	crate::metrics::spawn(prometheus_registry.as_ref(), &task_manager, client.clone(), backend.clone())
+		.map_err(|e| ServiceError::Other(format!("Failed to register the betting metrics: {:?}", e)))?;
+
 	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		network: network.clone(),
 		client: client.clone(),
@@ -226,6 +306,133 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 	Ok(task_manager)
 }
 
+/// Builds a full node that seals blocks with manual seal, for fast local testing.
+fn new_full_sealing(
+	config: Configuration,
+	ocw_key_seed: Option<String>,
+	sealing: Sealing,
+) -> Result<TaskManager, ServiceError> {
+	let (client, backend, keystore, mut task_manager) =
+		sc_service::new_full_parts::<Block, RuntimeApi, Executor>(&config)?;
+	let client = Arc::new(client);
+
+	insert_ocw_key(&keystore, ocw_key_seed.or_else(|| config.dev_key_seed.clone()))?;
+
+	let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+	let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+		config.transaction_pool.clone(),
+		config.prometheus_registry(),
+		task_manager.spawn_handle(),
+		client.clone(),
+	);
+
+	let import_queue = sc_consensus_manual_seal::import_queue(
+		Box::new(client.clone()),
+		&task_manager.spawn_handle(),
+		config.prometheus_registry(),
+	);
+
+	let inherent_data_providers = InherentDataProviders::new();
+	inherent_data_providers.register_provider(SealingTimestampProvider::new())
+		.map_err(|e| ServiceError::Other(format!("Failed to register the timestamp provider: {:?}", e)))?;
+
+	let (network, network_status_sinks, system_rpc_tx, network_starter) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue,
+			on_demand: None,
+			block_announce_validator_builder: None,
+			finality_proof_request_builder: None,
+			finality_proof_provider: None,
+		})?;
+
+	if config.offchain_worker.enabled {
+		sc_service::build_offchain_workers(
+			&config, backend.clone(), task_manager.spawn_handle(), client.clone(), network.clone(),
+		);
+	}
+
+	let (command_sink, commands_stream) = futures::channel::mpsc::channel(1024);
+	let prometheus_registry = config.prometheus_registry().cloned();
+
+	let rpc_extensions_builder = {
+		let client = client.clone();
+		let pool = transaction_pool.clone();
+		let command_sink = match sealing {
+			Sealing::Manual => Some(command_sink),
+			Sealing::Instant => None,
+		};
+
+		Box::new(move |deny_unsafe, _| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				command_sink: command_sink.clone(),
+			};
+
+			crate::rpc::create_full(deps)
+		})
+	};
+
+	crate::metrics::spawn(prometheus_registry.as_ref(), &task_manager, client.clone(), backend.clone())
+		.map_err(|e| ServiceError::Other(format!("Failed to register the betting metrics: {:?}", e)))?;
+
+	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		network,
+		client: client.clone(),
+		keystore,
+		task_manager: &mut task_manager,
+		transaction_pool: transaction_pool.clone(),
+		telemetry_connection_sinks: sc_service::TelemetryConnectionSinks::default(),
+		rpc_extensions_builder,
+		on_demand: None,
+		remote_blockchain: None,
+		backend, network_status_sinks, system_rpc_tx, config,
+	})?;
+
+	let env = sc_basic_authorship::ProposerFactory::new(
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+	);
+
+	match sealing {
+		Sealing::Instant => {
+			let authorship = sc_consensus_manual_seal::run_instant_seal(InstantSealParams {
+				block_import: client.clone(),
+				env,
+				client,
+				pool: transaction_pool.pool().clone(),
+				select_chain,
+				consensus_data_provider: None,
+				inherent_data_providers,
+			});
+			task_manager.spawn_essential_handle().spawn_blocking("instant-seal", authorship);
+		},
+		Sealing::Manual => {
+			let authorship = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+				block_import: client.clone(),
+				env,
+				client,
+				pool: transaction_pool.pool().clone(),
+				commands_stream,
+				select_chain,
+				consensus_data_provider: None,
+				inherent_data_providers,
+			});
+			task_manager.spawn_essential_handle().spawn_blocking("manual-seal", authorship);
+		},
+	}
+
+	network_starter.start_network();
+	Ok(task_manager)
+}
+
 /// Builds a new service for a light client.
 pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 	let (client, backend, keystore, mut task_manager, on_demand) =
@@ -0,0 +1,272 @@
+//! The `simulate` subcommand, which estimates how the pool fares under a betting workload.
+//!
+//! Every run builds a fresh chain state from a minimal genesis and drives the real pallet_chance
+//! and pallet_pooler code in `TestExternalities`: bets are placed through `bet` and settled through
+//! `scheduled_bet_callback` by a settler, and LPs deposit and withdraw through pallet_pooler. Bet
+//! outcomes are drawn with `--win-probability` instead of asking the oracle.
+
+use std::str::FromStr;
+use structopt::StructOpt;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use orml_traits::MultiCurrency;
+use sp_runtime::BuildStorage;
+use node_template_runtime::{
+	AccountId, Admin, AdminConfig, Balance, BalancesConfig, BlockNumber, Chance, ChanceConfig, GenesisConfig,
+	Origin, Pooler, PoolerConfig, SettlerBond, System, SystemConfig, Tokens, TokensConfig, TEST_USD_CURRENCY_ID,
+	pallet_admin::{Operation, ParameterChange},
+};
+
+/// How bet sizes are drawn.
+#[derive(Debug, Clone, Copy)]
+pub enum BetSizes {
+	/// Every bet stakes the same amount.
+	Fixed(Balance),
+	/// Stakes are uniform between two amounts, inclusive.
+	Uniform(Balance, Balance),
+	/// Stakes are exponentially distributed around a mean, like many small bets and a few
+	/// large ones.
+	Exponential(Balance),
+}
+
+impl FromStr for BetSizes {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let parse = |amount: &str| amount.parse::<Balance>().map_err(|e| format!("Invalid amount `{}`: {}", amount, e));
+		let parts: Vec<&str> = s.split(':').collect();
+		match parts.as_slice() {
+			["fixed", amount] => Ok(BetSizes::Fixed(parse(amount)?)),
+			["uniform", min, max] => {
+				let (min, max) = (parse(min)?, parse(max)?);
+				if min > max {
+					return Err("The minimum of a uniform bet size exceeds the maximum".into());
+				}
+				Ok(BetSizes::Uniform(min, max))
+			},
+			["exp", mean] => Ok(BetSizes::Exponential(parse(mean)?)),
+			_ => Err(format!("Unknown bet sizes `{}`, expected `fixed:<amount>`, `uniform:<min>:<max>` or `exp:<mean>`", s)),
+		}
+	}
+}
+
+impl BetSizes {
+	fn sample(&self, rng: &mut StdRng) -> Balance {
+		match *self {
+			BetSizes::Fixed(amount) => amount,
+			BetSizes::Uniform(min, max) => rng.gen_range(min, max + 1),
+			BetSizes::Exponential(mean) => {
+				let u: f64 = rng.gen_range(f64::EPSILON, 1.0);
+				(-(mean as f64) * u.ln()) as Balance
+			},
+		}
+	}
+}
+
+/// Run Monte Carlo simulations of betting against the pool.
+#[derive(Debug, StructOpt)]
+pub struct SimulateCmd {
+	/// The number of independent runs.
+	#[structopt(long, default_value = "100")]
+	pub runs: u32,
+
+	/// The number of blocks in each run.
+	#[structopt(long, default_value = "1000")]
+	pub blocks: BlockNumber,
+
+	/// The number of bets placed in each block.
+	#[structopt(long, default_value = "10")]
+	pub bets_per_block: u32,
+
+	/// How bet sizes are drawn: `fixed:<amount>`, `uniform:<min>:<max>` or `exp:<mean>`.
+	#[structopt(long, default_value = "exp:1000000000000")]
+	pub bet_sizes: BetSizes,
+
+	/// The chance that a bet wins. Bets pay out even money, so the edge comes from fees alone
+	/// at 0.5.
+	#[structopt(long, default_value = "0.5")]
+	pub win_probability: f64,
+
+	/// The liquidity the pool starts with.
+	#[structopt(long, default_value = "1000000000000000")]
+	pub pool: Balance,
+
+	/// Each block an LP deposits or withdraws, with equal chance, up to this amount.
+	#[structopt(long, default_value = "0")]
+	pub lp_flow: Balance,
+
+	/// The fee multiplier. Defaults to the runtime's.
+	#[structopt(long)]
+	pub fee_multiplier: Option<u128>,
+
+	/// The decimals fees are scaled by. Defaults to the runtime's.
+	#[structopt(long)]
+	pub system_decimals: Option<u128>,
+
+	/// The seed of the random number generator, for reproducible results.
+	#[structopt(long, default_value = "0")]
+	pub seed: u64,
+}
+
+/// What happened in one run.
+struct RunOutcome {
+	/// The change in pool value not caused by LP deposits and withdrawals.
+	house_pnl: i128,
+	/// The largest drop of the pool value from its peak, as a share of the peak.
+	max_drawdown: f64,
+	/// Whether a winning bet couldn't be paid out.
+	insolvent: bool,
+	/// Whether the circuit breaker paused betting.
+	tripped: bool,
+	placed: u64,
+	rejected: u64,
+}
+
+const PLAYER: [u8; 32] = [1; 32];
+const LP: [u8; 32] = [2; 32];
+const SETTLER: [u8; 32] = [3; 32];
+
+impl SimulateCmd {
+	/// Run the simulations and print a summary.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		if !(0.0..=1.0).contains(&self.win_probability) {
+			return Err(sc_cli::Error::Input("The win probability must be between 0 and 1".into()));
+		}
+		let mut rng = StdRng::seed_from_u64(self.seed);
+		let outcomes = (0..self.runs)
+			.map(|_| self.simulate_run(&mut rng))
+			.collect::<Result<Vec<_>, _>>()?;
+		report(&outcomes);
+		Ok(())
+	}
+
+	fn genesis(&self) -> GenesisConfig {
+		let (player, lp, settler) = (AccountId::from(PLAYER), AccountId::from(LP), AccountId::from(SETTLER));
+		let mut parameters = Vec::new();
+		if let Some(multiplier) = self.fee_multiplier {
+			parameters.push(ParameterChange::FeeMultiplier(multiplier));
+		}
+		if let Some(decimals) = self.system_decimals {
+			parameters.push(ParameterChange::SystemDecimals(decimals));
+		}
+		GenesisConfig {
+			frame_system: Some(SystemConfig { code: vec![], changes_trie_config: None }),
+			// The settler only needs its bond.
+			pallet_balances: Some(BalancesConfig { balances: vec![(settler.clone(), SettlerBond::get())] }),
+			orml_tokens: Some(TokensConfig {
+				endowed_accounts: vec![
+					(player, TEST_USD_CURRENCY_ID, Balance::max_value() / 4),
+					(lp.clone(), TEST_USD_CURRENCY_ID, Balance::max_value() / 4),
+				],
+			}),
+			pallet_aura: None,
+			pallet_grandpa: None,
+			pallet_sudo: None,
+			pallet_pooler: Some(PoolerConfig {
+				positions: vec![(TEST_USD_CURRENCY_ID, lp, self.pool)],
+				reserves: vec![],
+			}),
+			pallet_chance: Some(ChanceConfig { parameters, treasury: None, paused: vec![], oracle_key: None }),
+			pallet_admin: Some(AdminConfig { settlers: vec![settler], ..Default::default() }),
+		}
+	}
+
+	fn simulate_run(&self, rng: &mut StdRng) -> sc_cli::Result<RunOutcome> {
+		let storage = self.genesis().build_storage().map_err(sc_cli::Error::Input)?;
+		let mut ext = sp_io::TestExternalities::new(storage);
+		let (player, lp, settler) = (AccountId::from(PLAYER), AccountId::from(LP), AccountId::from(SETTLER));
+		let pool_value = || Tokens::free_balance(TEST_USD_CURRENCY_ID, &Chance::account_id());
+
+		Ok(ext.execute_with(|| {
+			let mut outcome = RunOutcome {
+				house_pnl: 0, max_drawdown: 0.0, insolvent: false, tripped: false, placed: 0, rejected: 0,
+			};
+			let start = pool_value();
+			let mut peak = start;
+			let mut net_lp_flow: i128 = 0;
+
+			for block in 1..=self.blocks {
+				System::set_block_number(block);
+				System::reset_events();
+
+				if self.lp_flow > 0 {
+					let amount = rng.gen_range(0, self.lp_flow + 1);
+					let before = pool_value();
+					if rng.gen_bool(0.5) {
+						let _ = Pooler::deposit(Origin::signed(lp.clone()), TEST_USD_CURRENCY_ID, amount);
+					} else {
+						let units = amount.min(Pooler::balance(TEST_USD_CURRENCY_ID, lp.clone()));
+						let _ = Pooler::withdraw(Origin::signed(lp.clone()), TEST_USD_CURRENCY_ID, units);
+					}
+					net_lp_flow += pool_value() as i128 - before as i128;
+				}
+
+				let mut placed = Vec::new();
+				for _ in 0..self.bets_per_block {
+					let id = Chance::next_bet_id();
+					let amount = self.bet_sizes.sample(rng);
+					match Chance::bet(Origin::signed(player.clone()), TEST_USD_CURRENCY_ID, amount) {
+						Ok(_) => {
+							outcome.placed += 1;
+							placed.push(id);
+						},
+						Err(_) => outcome.rejected += 1,
+					}
+				}
+				for id in placed {
+					let won = rng.gen_bool(self.win_probability);
+					if Chance::scheduled_bet_callback(Origin::signed(settler.clone()), id, won).is_err() {
+						outcome.insolvent = true;
+					}
+				}
+
+				let value = pool_value();
+				peak = peak.max(value);
+				if peak > 0 {
+					outcome.max_drawdown = outcome.max_drawdown.max((peak - value) as f64 / peak as f64);
+				}
+				if value == 0 {
+					outcome.insolvent = true;
+				}
+				if outcome.insolvent {
+					break;
+				}
+			}
+
+			outcome.tripped = Admin::is_paused(Operation::Bet);
+			outcome.house_pnl = pool_value() as i128 - start as i128 - net_lp_flow;
+			outcome
+		}))
+	}
+}
+
+fn percentile<T: Copy>(sorted: &[T], p: f64) -> T {
+	sorted[((sorted.len() - 1) as f64 * p).round() as usize]
+}
+
+fn report(outcomes: &[RunOutcome]) {
+	if outcomes.is_empty() {
+		println!("No runs.");
+		return;
+	}
+	let runs = outcomes.len() as f64;
+	let share = |f: &dyn Fn(&RunOutcome) -> bool| outcomes.iter().filter(|o| f(o)).count() as f64 / runs;
+
+	let mut pnl: Vec<i128> = outcomes.iter().map(|o| o.house_pnl).collect();
+	pnl.sort();
+	let mean_pnl = pnl.iter().map(|p| *p as f64).sum::<f64>() / runs;
+
+	let mut drawdowns: Vec<f64> = outcomes.iter().map(|o| o.max_drawdown).collect();
+	drawdowns.sort_by(|a, b| a.partial_cmp(b).expect("drawdowns are finite; qed"));
+
+	let placed: u64 = outcomes.iter().map(|o| o.placed).sum();
+	let rejected: u64 = outcomes.iter().map(|o| o.rejected).sum();
+
+	println!("runs: {}, bets placed: {}, bets rejected: {}", outcomes.len(), placed, rejected);
+	println!("house P&L: mean {:.0}, p5 {}, median {}, p95 {}",
+		mean_pnl, percentile(&pnl, 0.05), percentile(&pnl, 0.5), percentile(&pnl, 0.95));
+	println!("max drawdown: median {:.2}%, p95 {:.2}%, p99 {:.2}%, worst {:.2}%",
+		100.0 * percentile(&drawdowns, 0.5), 100.0 * percentile(&drawdowns, 0.95),
+		100.0 * percentile(&drawdowns, 0.99), 100.0 * percentile(&drawdowns, 1.0));
+	println!("P(circuit breaker trips): {:.2}%", 100.0 * share(&|o| o.tripped));
+	println!("P(pool insolvency): {:.2}%", 100.0 * share(&|o| o.insolvent));
+}
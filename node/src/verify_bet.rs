@@ -0,0 +1,70 @@
+//! The `verify-bet` subcommand, which recomputes the outcome of a provably-fair bet offline.
+
+use structopt::StructOpt;
+use sp_core::{bytes::from_hex, crypto::Ss58Codec, sr25519};
+use pallet_chance_fairness::{roll, verify, Reveal, Verdict};
+
+/// Recompute a bet's outcome from the inputs returned by `chance_verifyBet`.
+#[derive(Debug, StructOpt)]
+pub struct VerifyBetCmd {
+	/// The id of the bet.
+	#[structopt(long)]
+	pub bet_id: u64,
+
+	/// The oracle's public key, in SS58 or hex.
+	#[structopt(long)]
+	pub oracle: String,
+
+	/// The index of the oracle seed assigned to the bet.
+	#[structopt(long)]
+	pub index: u64,
+
+	/// The oracle's seed, in hex.
+	#[structopt(long)]
+	pub seed: String,
+
+	/// The oracle's signature of the seed, in hex.
+	#[structopt(long)]
+	pub signature: String,
+
+	/// The hash of the block the bet's cancellation window closed in, in hex.
+	#[structopt(long)]
+	pub block_hash: String,
+}
+
+impl VerifyBetCmd {
+	/// Print the recomputed outcome, failing if the oracle didn't sign the seed for the bet.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let decode = |name: &str, value: &str| {
+			from_hex(value).map_err(|e| sc_cli::Error::Input(format!("Invalid {}: {:?}", name, e)))
+		};
+		let oracle = match sr25519::Public::from_ss58check(&self.oracle) {
+			Ok(oracle) => oracle,
+			Err(_) => {
+				let bytes = decode("oracle key", &self.oracle)?;
+				if bytes.len() != 32 {
+					return Err(sc_cli::Error::Input("Invalid oracle key: expected 32 bytes".into()));
+				}
+				let mut raw = [0u8; 32];
+				raw.copy_from_slice(&bytes);
+				sr25519::Public::from_raw(raw)
+			},
+		};
+		let reveal = Reveal {
+			index: self.index,
+			seed: decode("seed", &self.seed)?,
+			signature: decode("signature", &self.signature)?,
+		};
+		let block_hash = decode("block hash", &self.block_hash)?;
+
+		println!("roll: 0x{}", roll(&reveal.seed, &block_hash, self.bet_id).iter().map(|b| format!("{:02x}", b)).collect::<String>());
+		match verify(&oracle, &reveal, &block_hash, self.bet_id) {
+			Verdict::Won => println!("bet {} won", self.bet_id),
+			Verdict::Lost => println!("bet {} lost", self.bet_id),
+			Verdict::InvalidSignature => {
+				return Err(sc_cli::Error::Input("The oracle didn't sign the seed for this bet and index".into()));
+			},
+		}
+		Ok(())
+	}
+}
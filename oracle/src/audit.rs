@@ -0,0 +1,49 @@
+//! The append-only audit log of every value the oracle issues.
+//!
+//! Each line is a JSON `Entry`. The file is only ever opened for appending, and every entry is
+//! synced to disk before the value it records is served.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use serde::Serialize;
+
+/// One issued value.
+#[derive(Debug, Serialize)]
+pub struct Entry<'a> {
+	/// When the value was issued, in milliseconds since the Unix epoch.
+	pub time: u128,
+	/// The path and query of the request.
+	pub request: &'a str,
+	/// The response body.
+	pub response: &'a str,
+	/// The hex signature of the response.
+	pub signature: &'a str,
+	/// The commitment of the chain a seed was revealed from.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub commitment: Option<&'a str>,
+	/// The global index of a revealed seed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub index: Option<u64>,
+}
+
+/// The audit log file.
+pub struct AuditLog {
+	file: File,
+}
+
+impl AuditLog {
+	/// Open the log at `path` for appending, creating it if needed.
+	pub fn open(path: &Path) -> io::Result<Self> {
+		let file = OpenOptions::new().create(true).append(true).open(path)?;
+		Ok(AuditLog { file })
+	}
+
+	/// Append `entry` and sync it to disk.
+	pub fn append(&mut self, entry: &Entry) -> io::Result<()> {
+		let mut line = serde_json::to_vec(entry)?;
+		line.push(b'\n');
+		self.file.write_all(&line)?;
+		self.file.sync_data()
+	}
+}
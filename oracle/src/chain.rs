@@ -0,0 +1,114 @@
+//! The commit–reveal seed chains.
+//!
+//! A chain is built backwards from a random tip: every seed is the SHA-256 hash of the one after
+//! it. The first entry, the commitment, is published before any seed is revealed. Once committed
+//! the oracle can't choose the seeds it hands out, and anyone holding the commitment can check the
+//! seed at position `p` by hashing it `p` times.
+//!
+//! pallet_chance assigns every bet and round a global seed index. Each chain covers the `length`
+//! indices from its `offset`, and the next chain starts where it ends.
+
+use sp_core::hashing::sha2_256;
+
+/// A seed of the chain.
+pub type Seed = [u8; 32];
+
+/// A chain of pre-committed seeds.
+pub struct SeedChain {
+	/// The commitment followed by the seeds in reveal order. The last one is the tip.
+	seeds: Vec<Seed>,
+	/// The global index of the first seed after the commitment.
+	offset: u64,
+}
+
+impl SeedChain {
+	/// Build the chain of `length` seeds ending in `tip`, covering the indices from `offset`.
+	pub fn new(tip: Seed, length: usize, offset: u64) -> Self {
+		let mut seeds = Vec::with_capacity(length + 1);
+		seeds.push(tip);
+		for _ in 0..length {
+			let previous = sha2_256(seeds.last().expect("the tip was pushed; qed"));
+			seeds.push(previous);
+		}
+		seeds.reverse();
+		SeedChain { seeds, offset }
+	}
+
+	/// The published start of the chain.
+	pub fn commitment(&self) -> Seed {
+		self.seeds[0]
+	}
+
+	/// The last seed of the chain, which all others are derived from.
+	pub fn tip(&self) -> Seed {
+		self.seeds[self.seeds.len() - 1]
+	}
+
+	/// The number of seeds in the chain.
+	pub fn length(&self) -> usize {
+		self.seeds.len() - 1
+	}
+
+	/// The global index of the chain's first seed.
+	pub fn offset(&self) -> u64 {
+		self.offset
+	}
+
+	/// The global index right after the chain's last seed.
+	pub fn end(&self) -> u64 {
+		self.offset + self.length() as u64
+	}
+
+	/// Whether the seed at global `index` is in this chain.
+	pub fn contains(&self, index: u64) -> bool {
+		index >= self.offset && index < self.end()
+	}
+
+	/// The seed at global `index` and its position in the chain, or `None` if it isn't in this
+	/// chain.
+	pub fn seed(&self, index: u64) -> Option<(usize, Seed)> {
+		if !self.contains(index) {
+			return None;
+		}
+		let position = (index - self.offset) as usize + 1;
+		Some((position, self.seeds[position]))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_every_seed_hashes_to_the_one_before_it() {
+		let chain = SeedChain::new([7; 32], 5, 10);
+		assert_eq!(chain.length(), 5);
+		assert_eq!(chain.tip(), [7; 32]);
+		let mut previous = chain.commitment();
+		for index in 10..15 {
+			let (position, seed) = chain.seed(index).unwrap();
+			assert_eq!(position as u64, index - 9);
+			assert_eq!(sha2_256(&seed), previous);
+			previous = seed;
+		}
+		assert_eq!(previous, chain.tip());
+	}
+
+	#[test]
+	fn test_chain_covers_its_indices_only() {
+		let chain = SeedChain::new([1; 32], 3, 4);
+		assert_eq!((chain.offset(), chain.end()), (4, 7));
+		assert!(chain.seed(3).is_none());
+		assert!(chain.seed(4).is_some());
+		assert!(chain.seed(6).is_some());
+		assert!(chain.seed(7).is_none());
+	}
+
+	#[test]
+	fn test_chain_is_rebuilt_from_its_tip() {
+		let chain = SeedChain::new([3; 32], 4, 0);
+		let rebuilt = SeedChain::new(chain.tip(), chain.length(), chain.offset());
+		assert_eq!(rebuilt.commitment(), chain.commitment());
+		assert_eq!(rebuilt.seed(2), chain.seed(2));
+	}
+}
@@ -0,0 +1,182 @@
+//! The randomness oracle queried by pallet_ocw.
+//!
+//! `GET /random?<kind>=<id>&index=<i>` reveals the seed at global index `i` of the commit–reveal
+//! chains for a `bet`, `lottery` or `prediction` subject, as `{"seed", "index", "signature",
+//! "commitment"}`. `GET /commitment[?index=<i>]` reports the chain covering `i`, or the current
+//! one, and the oracle's public key. A seed is only revealed once the node at `--node-rpc` reports
+//! it due for the subject, and a seed request is refused otherwise. Every response carries its
+//! sr25519 signature in the `X-Oracle-Signature` header, and every issued value is appended to
+//! the audit log.
+#![warn(missing_docs)]
+
+mod audit;
+mod chain;
+mod node;
+mod oracle;
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use hyper::{Body, Method, Request, Response, Server, StatusCode, Uri};
+use hyper::service::{make_service_fn, service_fn};
+use pallet_chance_fairness::Subject;
+use sp_core::{sr25519, Pair};
+use structopt::StructOpt;
+use crate::audit::AuditLog;
+use crate::node::Node;
+use crate::oracle::Oracle;
+
+/// The header carrying the signature of a response.
+const SIGNATURE_HEADER: &str = "X-Oracle-Signature";
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "chance-oracle")]
+struct Opt {
+	/// The address to listen on.
+	#[structopt(long, default_value = "127.0.0.1:3000")]
+	listen: SocketAddr,
+
+	/// The secret URI of the sr25519 key responses are signed with, e.g. a mnemonic or `//Alice`.
+	#[structopt(long)]
+	suri: String,
+
+	/// The number of seeds committed to at a time.
+	#[structopt(long, default_value = "100000")]
+	chain_length: usize,
+
+	/// Where the current chain is kept across restarts. It holds the chain's secret tip.
+	#[structopt(long, default_value = "oracle-state.json", parse(from_os_str))]
+	state: PathBuf,
+
+	/// The append-only log of every issued value.
+	#[structopt(long, default_value = "oracle-audit.log", parse(from_os_str))]
+	audit_log: PathBuf,
+
+	/// The JSON-RPC endpoint of the node asked whether a seed may be revealed.
+	#[structopt(long, default_value = "http://127.0.0.1:9933")]
+	node_rpc: Uri,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+	let opt = Opt::from_args();
+
+	let pair = sr25519::Pair::from_string(&opt.suri, None)
+		.map_err(|e| format!("Invalid secret URI: {:?}", e))?;
+	let audit = AuditLog::open(&opt.audit_log)?;
+	let oracle = Oracle::new(pair, opt.chain_length, opt.state, audit)?;
+	log::info!("Signing responses with {}", oracle.public_key());
+	let oracle = Arc::new(Mutex::new(oracle));
+	let node = Arc::new(Node::new(opt.node_rpc));
+
+	let make_service = make_service_fn(move |_| {
+		let oracle = oracle.clone();
+		let node = node.clone();
+		async move {
+			Ok::<_, Infallible>(service_fn(move |request| {
+				let oracle = oracle.clone();
+				let node = node.clone();
+				async move { Ok::<_, Infallible>(respond(&oracle, &node, request).await) }
+			}))
+		}
+	});
+
+	log::info!("Listening on http://{}", opt.listen);
+	Server::bind(&opt.listen).serve(make_service).await?;
+	Ok(())
+}
+
+/// Serve `request`, first asking the node whether a requested seed is due.
+async fn respond(oracle: &Mutex<Oracle>, node: &Node, request: Request<Body>) -> Response<Body> {
+	if request.method() == Method::GET && request.uri().path() == "/random" {
+		if let Some((subject, index)) = seed_request(request.uri().query()) {
+			match node.seed_due(subject, index).await {
+				Ok(true) => {},
+				Ok(false) => {
+					log::warn!("Refused {}: the seed isn't due", request.uri());
+					return status(StatusCode::BAD_REQUEST);
+				},
+				Err(e) => {
+					log::error!("Cannot ask the node about {}: {}", request.uri(), e);
+					return status(StatusCode::SERVICE_UNAVAILABLE);
+				},
+			}
+		}
+	}
+	serve(oracle, request)
+}
+
+/// Serve `request`. The lock is held while a value is issued so the audit log follows the order
+/// values are handed out in.
+fn serve(oracle: &Mutex<Oracle>, request: Request<Body>) -> Response<Body> {
+	let target = request.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/").to_string();
+	if request.method() != Method::GET {
+		return status(StatusCode::METHOD_NOT_ALLOWED);
+	}
+	let mut oracle = match oracle.lock() {
+		Ok(oracle) => oracle,
+		Err(_) => return status(StatusCode::INTERNAL_SERVER_ERROR),
+	};
+	let query = request.uri().query();
+	let issued = match request.uri().path() {
+		"/random" => match seed_request(query) {
+			Some((subject, index)) => oracle.seed(&target, subject, index),
+			None => return status(StatusCode::BAD_REQUEST),
+		},
+		"/commitment" => {
+			let index = match query_param(query, "index").map(str::parse::<u64>) {
+				Some(Ok(index)) => Some(index),
+				Some(Err(_)) => return status(StatusCode::BAD_REQUEST),
+				None => None,
+			};
+			match oracle.commitment(&target, index) {
+				Some(response) => Ok(response),
+				None => return status(StatusCode::NOT_FOUND),
+			}
+		},
+		_ => return status(StatusCode::NOT_FOUND),
+	};
+	match issued {
+		Ok(issued) => {
+			log::debug!("{} -> {}", target, issued.body);
+			Response::builder()
+				.header(SIGNATURE_HEADER, issued.signature)
+				.body(Body::from(issued.body))
+				.unwrap_or_else(|_| status(StatusCode::INTERNAL_SERVER_ERROR))
+		},
+		Err(e) if e.kind() == std::io::ErrorKind::InvalidInput => {
+			log::warn!("Refused {}: {}", target, e);
+			status(StatusCode::BAD_REQUEST)
+		},
+		Err(e) => {
+			// Nothing is served unless it was persisted and logged.
+			log::error!("Cannot issue a value for {}: {}", target, e);
+			status(StatusCode::INTERNAL_SERVER_ERROR)
+		},
+	}
+}
+
+/// The subject and seed index of a `/random` seed request.
+fn seed_request(query: Option<&str>) -> Option<(Subject, u64)> {
+	let index = query_param(query, "index")?.parse().ok()?;
+	let subject = ["bet", "lottery", "prediction"].iter().find_map(|kind| {
+		let id = query_param(query, kind)?.parse().ok()?;
+		Subject::from_request(kind, id)
+	})?;
+	Some((subject, index))
+}
+
+fn query_param<'a>(query: Option<&'a str>, name: &str) -> Option<&'a str> {
+	query?.split('&').find_map(|pair| {
+		let mut parts = pair.splitn(2, '=');
+		if parts.next()? == name { parts.next() } else { None }
+	})
+}
+
+fn status(code: StatusCode) -> Response<Body> {
+	let mut response = Response::new(Body::empty());
+	*response.status_mut() = code;
+	response
+}
@@ -0,0 +1,81 @@
+//! Asking the node whether a seed may be revealed.
+//!
+//! A seed is only revealed once pallet_chance has assigned its index to the subject it is asked
+//! for and the subject's outcome can no longer change, e.g. once a bet's cancellation window has
+//! closed. Revealing a seed any earlier would let a better cancel a losing bet, and revealing an
+//! index nobody was assigned yet would give away every seed below it in its chain. The node
+//! answers with the `chance_seedDue` RPC at its best block.
+
+use std::io;
+use hyper::{body, client::HttpConnector, Body, Client, Method, Request, Uri};
+use pallet_chance_fairness::Subject;
+
+/// A JSON-RPC client of the node.
+pub struct Node {
+	client: Client<HttpConnector>,
+	url: Uri,
+}
+
+impl Node {
+	/// A client of the node serving JSON-RPC over HTTP at `url`.
+	pub fn new(url: Uri) -> Self {
+		Node { client: Client::new(), url }
+	}
+
+	/// Whether the seed at `index` may be revealed for `subject`.
+	pub async fn seed_due(&self, subject: Subject, index: u64) -> io::Result<bool> {
+		let request = Request::builder()
+			.method(Method::POST)
+			.uri(self.url.clone())
+			.header("Content-Type", "application/json")
+			.body(Body::from(seed_due_call(subject, index)))
+			.map_err(other)?;
+		let response = self.client.request(request).await.map_err(other)?;
+		let reply = body::to_bytes(response.into_body()).await.map_err(other)?;
+		seed_due_reply(&reply)
+	}
+}
+
+/// The JSON-RPC call asking whether the seed at `index` is due for `subject`.
+fn seed_due_call(subject: Subject, index: u64) -> String {
+	serde_json::json!({
+		"jsonrpc": "2.0",
+		"id": 1,
+		"method": "chance_seedDue",
+		"params": [subject, index],
+	}).to_string()
+}
+
+/// The answer in the node's reply to a `chance_seedDue` call.
+fn seed_due_reply(reply: &[u8]) -> io::Result<bool> {
+	let reply: serde_json::Value = serde_json::from_slice(reply)?;
+	if let Some(error) = reply.get("error") {
+		return Err(other(format!("the node returned an error: {}", error)));
+	}
+	reply["result"].as_bool().ok_or_else(|| other("the node's reply has no result"))
+}
+
+fn other(e: impl ToString) -> io::Error {
+	io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_seed_due_calls_carry_the_subject_and_index() {
+		let call: serde_json::Value = serde_json::from_str(&seed_due_call(Subject::Lottery(2), 7)).unwrap();
+		assert_eq!(call["method"], "chance_seedDue");
+		assert_eq!(call["params"], serde_json::json!([{ "Lottery": 2 }, 7]));
+	}
+
+	#[test]
+	fn test_seed_due_replies_are_parsed() {
+		assert!(seed_due_reply(br#"{"jsonrpc":"2.0","result":true,"id":1}"#).unwrap());
+		assert!(!seed_due_reply(br#"{"jsonrpc":"2.0","result":false,"id":1}"#).unwrap());
+		let error = br#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+		assert!(seed_due_reply(error).is_err());
+		assert!(seed_due_reply(b"not json").is_err());
+	}
+}
@@ -0,0 +1,316 @@
+//! Issuing signed random values.
+//!
+//! Seeds are revealed by index from the `SeedChain` covering it. When an index past the last chain
+//! is asked for, a new chain is built from a fresh tip and its commitment is logged before any of
+//! its seeds is issued. Lottery tickets and prediction outcomes are drawn on chain from their
+//! round's seed.
+//!
+//! Every seed is signed with the oracle's sr25519 key together with the subject and index it was
+//! revealed for, which is what pallet_chance checks on chain. Every response is also signed over
+//! the request's path and query followed by a newline and the response body, so a response can't
+//! be replayed for another request. The chains are persisted before a seed is served, so a restart
+//! serves the same seed at every index.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use pallet_chance_fairness::{signed_message, Subject};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sp_core::{sr25519, Pair};
+use crate::audit::{AuditLog, Entry};
+use crate::chain::{Seed, SeedChain};
+
+/// A persisted chain. The tip is secret: anyone who reads it can predict every unrevealed seed.
+#[derive(Serialize, Deserialize)]
+struct ChainState {
+	tip: String,
+	length: usize,
+	offset: u64,
+}
+
+/// The persisted chains, oldest first, and the index after the highest one served.
+#[derive(Serialize, Deserialize)]
+struct State {
+	chains: Vec<ChainState>,
+	next: u64,
+}
+
+/// A signed response.
+pub struct Response {
+	pub body: String,
+	pub signature: String,
+}
+
+/// The oracle's key, seed chains and audit log.
+pub struct Oracle {
+	pair: sr25519::Pair,
+	/// Every chain committed to, oldest first. Earlier chains are kept so their seeds can be
+	/// served again.
+	chains: Vec<SeedChain>,
+	chain_length: usize,
+	next: u64,
+	state_path: PathBuf,
+	audit: AuditLog,
+}
+
+impl Oracle {
+	/// Resume the chains persisted at `state_path`, or commit to a new one of `chain_length`
+	/// seeds.
+	pub fn new(
+		pair: sr25519::Pair,
+		chain_length: usize,
+		state_path: PathBuf,
+		audit: AuditLog,
+	) -> io::Result<Self> {
+		let chain_length = chain_length.max(1);
+		let state = match fs::read(&state_path) {
+			Ok(bytes) => serde_json::from_slice(&bytes)?,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => State { chains: Vec::new(), next: 0 },
+			Err(e) => return Err(e),
+		};
+		let chains = state.chains.iter()
+			.map(|chain| {
+				let tip = decode_seed(&chain.tip)
+					.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid tip in the state file"))?;
+				Ok(SeedChain::new(tip, chain.length, chain.offset))
+			})
+			.collect::<io::Result<Vec<_>>>()?;
+		let mut oracle = Oracle { pair, chains, chain_length, next: state.next, state_path, audit };
+		if oracle.chains.is_empty() {
+			oracle.commit()?;
+		}
+		Ok(oracle)
+	}
+
+	/// The hex public key seeds and responses are signed with.
+	pub fn public_key(&self) -> String {
+		format!("0x{}", hex::encode(self.pair.public()))
+	}
+
+	/// Reveal the seed at `index` for `subject`, as the JSON the offchain worker expects. The
+	/// caller must have checked with the node that the seed is due. Seeds are only committed one
+	/// chain ahead, so an index past the next chain is refused with `InvalidInput`.
+	pub fn seed(&mut self, request: &str, subject: Subject, index: u64) -> io::Result<Response> {
+		if index >= self.current().end() + self.chain_length as u64 {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "the index is too far ahead"));
+		}
+		if index >= self.current().end() {
+			self.commit()?;
+		}
+		let chain = self.chains.iter().find(|chain| chain.contains(index))
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the index predates the first chain"))?;
+		let (_, seed) = chain.seed(index).expect("the chain contains the index; qed");
+		let commitment = hex::encode(chain.commitment());
+		self.next = self.next.max(index + 1);
+		self.persist()?;
+		let signature = self.pair.sign(&signed_message(subject, index, &seed));
+		let body = serde_json::json!({
+			"seed": hex::encode(seed),
+			"index": index,
+			"signature": hex::encode(signature),
+			"commitment": commitment,
+		}).to_string();
+		self.issue(request, body, Some(&commitment), Some(index))
+	}
+
+	/// The commitment of the chain covering `index`, or of the current chain, for verifiers.
+	pub fn commitment(&self, request: &str, index: Option<u64>) -> Option<Response> {
+		let chain = match index {
+			Some(index) => self.chains.iter().find(|chain| chain.contains(index))?,
+			None => self.current(),
+		};
+		let body = serde_json::json!({
+			"public_key": self.public_key(),
+			"commitment": hex::encode(chain.commitment()),
+			"offset": chain.offset(),
+			"length": chain.length(),
+			"next": self.next,
+		}).to_string();
+		Some(self.sign(request, body))
+	}
+
+	/// The chain new indices are revealed from.
+	fn current(&self) -> &SeedChain {
+		self.chains.last().expect("a chain is committed on start; qed")
+	}
+
+	/// Start a new chain after the current one, persist it and log its commitment.
+	fn commit(&mut self) -> io::Result<()> {
+		let offset = self.chains.last().map_or(0, |chain| chain.end());
+		let mut tip: Seed = [0; 32];
+		OsRng.fill_bytes(&mut tip);
+		self.chains.push(SeedChain::new(tip, self.chain_length, offset));
+		self.persist()?;
+		let commitment = hex::encode(self.current().commitment());
+		log::info!("Committed to seed chain 0x{} of {} seeds from index {}", commitment, self.chain_length, offset);
+		let body = serde_json::json!({
+			"commitment": commitment,
+			"offset": offset,
+			"length": self.chain_length,
+		}).to_string();
+		self.issue("commit", body, Some(&commitment), None).map(|_| ())
+	}
+
+	/// Persist the chains, replacing the state file atomically.
+	fn persist(&self) -> io::Result<()> {
+		let state = State {
+			chains: self.chains.iter()
+				.map(|chain| ChainState {
+					tip: hex::encode(chain.tip()),
+					length: chain.length(),
+					offset: chain.offset(),
+				})
+				.collect(),
+			next: self.next,
+		};
+		let temporary = self.state_path.with_extension("tmp");
+		fs::write(&temporary, serde_json::to_vec(&state)?)?;
+		fs::rename(&temporary, &self.state_path)
+	}
+
+	/// Sign `body` and append it to the audit log.
+	fn issue(
+		&mut self,
+		request: &str,
+		body: String,
+		commitment: Option<&str>,
+		index: Option<u64>,
+	) -> io::Result<Response> {
+		let response = self.sign(request, body);
+		self.audit.append(&Entry {
+			time: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or_default(),
+			request,
+			response: &response.body,
+			signature: &response.signature,
+			commitment,
+			index,
+		})?;
+		Ok(response)
+	}
+
+	fn sign(&self, request: &str, body: String) -> Response {
+		let mut message = Vec::with_capacity(request.len() + 1 + body.len());
+		message.extend_from_slice(request.as_bytes());
+		message.push(b'\n');
+		message.extend_from_slice(body.as_bytes());
+		let signature = format!("0x{}", hex::encode(self.pair.sign(&message)));
+		Response { body, signature }
+	}
+}
+
+fn decode_seed(s: &str) -> Option<Seed> {
+	let bytes = hex::decode(s.trim_start_matches("0x")).ok()?;
+	let mut seed: Seed = [0; 32];
+	if bytes.len() != seed.len() {
+		return None;
+	}
+	seed.copy_from_slice(&bytes);
+	Some(seed)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::Path;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use pallet_chance_fairness::Reveal;
+	use sp_core::hashing::sha2_256;
+
+	/// A fresh directory for one test's state and audit log.
+	fn scratch_dir() -> PathBuf {
+		static NEXT: AtomicUsize = AtomicUsize::new(0);
+		let dir = std::env::temp_dir().join(format!(
+			"chance-oracle-{}-{}",
+			std::process::id(),
+			NEXT.fetch_add(1, Ordering::SeqCst),
+		));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn pair() -> sr25519::Pair {
+		sr25519::Pair::from_seed(&[9; 32])
+	}
+
+	fn open(dir: &Path, chain_length: usize) -> Oracle {
+		let audit = AuditLog::open(&dir.join("audit.log")).unwrap();
+		Oracle::new(pair(), chain_length, dir.join("state.json"), audit).unwrap()
+	}
+
+	/// The reveal in a seed response.
+	fn reveal(response: &Response) -> Reveal {
+		let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+		let hex_field = |name: &str| hex::decode(body[name].as_str().unwrap()).unwrap();
+		Reveal { index: body["index"].as_u64().unwrap(), seed: hex_field("seed"), signature: hex_field("signature") }
+	}
+
+	#[test]
+	fn test_seeds_are_signed_for_their_subject_and_index() {
+		let dir = scratch_dir();
+		let mut oracle = open(&dir, 4);
+		let response = oracle.seed("/random?bet=3&index=1", Subject::Bet(3), 1).unwrap();
+		let revealed = reveal(&response);
+		assert_eq!(revealed.index, 1);
+		assert!(revealed.is_signed_by(&pair().public(), Subject::Bet(3)));
+		assert!(!revealed.is_signed_by(&pair().public(), Subject::Bet(4)));
+		assert!(!revealed.is_signed_by(&pair().public(), Subject::Lottery(3)));
+		assert!(!Reveal { index: 2, ..revealed.clone() }.is_signed_by(&pair().public(), Subject::Bet(3)));
+
+		// the response itself is signed over the request and the body
+		let mut message = b"/random?bet=3&index=1\n".to_vec();
+		message.extend_from_slice(response.body.as_bytes());
+		let mut signature = [0u8; 64];
+		signature.copy_from_slice(&hex::decode(response.signature.trim_start_matches("0x")).unwrap());
+		assert!(sr25519::Pair::verify(&sr25519::Signature::from_raw(signature), &message, &pair().public()));
+	}
+
+	#[test]
+	fn test_seeds_are_revealed_from_the_committed_chains() {
+		let dir = scratch_dir();
+		let mut oracle = open(&dir, 2);
+		let first = oracle.commitment("/commitment", None).unwrap();
+		let first: serde_json::Value = serde_json::from_str(&first.body).unwrap();
+		let seed0 = reveal(&oracle.seed("r", Subject::Bet(0), 0).unwrap()).seed;
+		let seed1 = reveal(&oracle.seed("r", Subject::Bet(1), 1).unwrap()).seed;
+		assert_eq!(hex::encode(sha2_256(&seed0)), first["commitment"].as_str().unwrap());
+		assert_eq!(sha2_256(&seed1).to_vec(), seed0);
+
+		// index 2 starts a new chain
+		let seed2 = reveal(&oracle.seed("r", Subject::Bet(2), 2).unwrap()).seed;
+		let second = oracle.commitment("/commitment?index=2", Some(2)).unwrap();
+		let second: serde_json::Value = serde_json::from_str(&second.body).unwrap();
+		assert_eq!(second["offset"], 2);
+		assert_eq!(hex::encode(sha2_256(&seed2)), second["commitment"].as_str().unwrap());
+		assert!(oracle.commitment("/commitment?index=4", Some(4)).is_none());
+		// an index past the next chain is refused
+		let e = oracle.seed("r", Subject::Bet(6), 6).err().unwrap();
+		assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+	}
+
+	#[test]
+	fn test_restart_serves_the_same_seeds() {
+		let dir = scratch_dir();
+		let mut oracle = open(&dir, 2);
+		let seeds = (0..3)
+			.map(|index| reveal(&oracle.seed("r", Subject::Bet(index), index).unwrap()).seed)
+			.collect::<Vec<_>>();
+		drop(oracle);
+
+		let mut oracle = open(&dir, 2);
+		for (index, seed) in seeds.into_iter().enumerate() {
+			let index = index as u64;
+			assert_eq!(reveal(&oracle.seed("r", Subject::Bet(index), index).unwrap()).seed, seed);
+		}
+		let commitment = oracle.commitment("/commitment", None).unwrap();
+		let commitment: serde_json::Value = serde_json::from_str(&commitment.body).unwrap();
+		assert_eq!(commitment["next"], 3);
+
+		// every issued value and commitment is in the audit log
+		let log = fs::read_to_string(dir.join("audit.log")).unwrap();
+		assert_eq!(log.lines().filter(|line| line.contains("\"request\":\"commit\"")).count(), 2);
+		assert_eq!(log.lines().filter(|line| line.contains("\"request\":\"r\"")).count(), 6);
+	}
+}
@@ -0,0 +1,63 @@
+//! RPC interface for the admin pallet.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_admin_runtime_api::{AdminApi as AdminRuntimeApi, SettlerStats};
+
+#[rpc]
+pub trait AdminApi<BlockHash, AccountId, BlockNumber> {
+	#[rpc(name = "admin_settlers")]
+	fn settlers(&self, at: Option<BlockHash>) -> Result<Vec<AccountId>>;
+
+	#[rpc(name = "admin_settlerStats")]
+	fn settler_stats(&self, who: AccountId, at: Option<BlockHash>) -> Result<SettlerStats<BlockNumber>>;
+}
+
+/// A struct that implements the `AdminApi`.
+pub struct Admin<C, B> {
+	client: Arc<C>,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> Admin<C, B> {
+	/// Create new `Admin` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(1),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, AccountId, BlockNumber> AdminApi<<Block as BlockT>::Hash, AccountId, BlockNumber> for Admin<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: AdminRuntimeApi<Block, AccountId, BlockNumber>,
+	AccountId: Codec,
+	BlockNumber: Codec,
+{
+	fn settlers(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.settlers(&at).map_err(|e| runtime_error("Unable to query settlers.", e))
+	}
+
+	fn settler_stats(&self, who: AccountId, at: Option<<Block as BlockT>::Hash>) -> Result<SettlerStats<BlockNumber>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.settler_stats(&at, who).map_err(|e| runtime_error("Unable to query settler stats.", e))
+	}
+}
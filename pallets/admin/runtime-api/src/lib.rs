@@ -0,0 +1,19 @@
+//! Runtime API definition for querying the settler set and settler performance.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+pub use pallet_admin::SettlerStats;
+
+sp_api::decl_runtime_apis! {
+    pub trait AdminApi<AccountId, BlockNumber> where
+        AccountId: Codec,
+        BlockNumber: Codec,
+    {
+        /// The current settlers.
+        fn settlers() -> Vec<AccountId>;
+        /// The performance counters of `who`.
+        fn settler_stats(who: AccountId) -> SettlerStats<BlockNumber>;
+    }
+}
@@ -16,28 +16,242 @@
 // limitations under the License.
 
 use sp_std::{prelude::*};
-use sp_runtime::{DispatchResult, DispatchError};
-use frame_support::{decl_module, decl_storage, decl_event, decl_error, ensure};
-use frame_support::traits::Get;
-use frame_system::{self as system, ensure_signed};
+use sp_runtime::{DispatchResult, DispatchError, Perbill, RuntimeDebug, traits::{BadOrigin, Saturating, Zero}};
+use codec::{Codec, Encode, Decode};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+use frame_support::{decl_module, decl_storage, decl_event, decl_error, ensure, Parameter, weights::Weight};
+use frame_support::traits::{ChangeMembers, Currency, EnsureOrigin, Get, OnUnbalanced, ReservableCurrency};
+use frame_support::traits::schedule::{DispatchTime, ScheduleNamed, HARD_DEADLINE};
+use sp_runtime::traits::Dispatchable;
+use frame_system::{self as system, ensure_signed, ensure_root};
+
+pub mod migrations;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+use migrations::Releases;
 
 pub trait Trait: system::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
     type MaxSettlers: Get<u32>;
+    /// The default maximum size of every role other than `Settler`.
+    type MaxRoleMembers: Get<u32>;
+    /// The origin allowed to manage every role and to configure roles.
+    type AdminOrigin: EnsureOrigin<Self::Origin>;
+    /// How long a scheduled settler removal waits before it takes effect.
+    type RemovalDelay: Get<Self::BlockNumber>;
+    /// Notified whenever the settler set changes.
+    type SettlersChanged: ChangeMembers<Self::AccountId>;
+    /// The currency settler bonds are reserved in.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// The bond reserved from an account when it becomes a settler.
+    type SettlerBond: Get<BalanceOf<Self>>;
+    /// Where slashed bonds go, normally the LP pool.
+    type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+    /// The number of blocks in a settlement era.
+    type EraLength: Get<Self::BlockNumber>;
+    /// How many late settlements a settler may make in an era before its bond is slashed.
+    type MaxMissedSettlements: Get<u32>;
+    /// The share of a settler's bond slashed when it misses too many settlements in an era.
+    type MissedSettlementSlash: Get<Perbill>;
+    /// Settlers whose average settlement latency exceeds this are dropped at the end of an era.
+    type MaxAverageLatency: Get<Self::BlockNumber>;
+    /// Settlers with more disputes than this are dropped at the end of an era.
+    type MaxDisputes: Get<u32>;
+    /// The runtime call that parameter changes are enacted through.
+    type Proposal: Parameter + Dispatchable<Origin=Self::Origin> + From<Call<Self>>;
+    /// Enacts parameter changes once their delay has passed.
+    type Scheduler: ScheduleNamed<Self::BlockNumber, Self::Proposal, Self::PalletsOrigin>;
+    /// The caller origin, overarching type of all pallets origins.
+    type PalletsOrigin: From<system::RawOrigin<Self::AccountId>> + Codec + Clone + Eq;
+    /// How long a scheduled parameter change waits before it is enacted.
+    type ParameterChangeDelay: Get<Self::BlockNumber>;
+}
+
+pub type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+type NegativeImbalanceOf<T> =
+    <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+
+/// Operations that can be paused independently of each other.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Operation {
+    Bet,
+    Deposit,
+    Withdraw,
+    Settle,
+}
+
+/// The scheduler id prefix of parameter changes.
+const PARAMETER_CHANGE_ID: [u8; 8] = *b"adminprm";
+
+/// The largest fee multiplier a parameter change may set.
+pub const MAX_FEE_MULTIPLIER: u128 = 100;
+
+/// The largest system decimals a parameter change may set.
+pub const MAX_SYSTEM_DECIMALS: u128 = 1_000_000_000_000_000_000;
+
+/// A change to one of the runtime-settable economic parameters.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum ParameterChange {
+    /// The multiplier of the fee taken from every bet.
+    FeeMultiplier(u128),
+    /// The decimals bet fees are scaled by.
+    SystemDecimals(u128),
+    /// The maximum number of settlers.
+    MaxSettlers(u32),
+    /// \[min_bet, max_bet, max_bet_pool_ratio\]
+    BetLimits(u128, u128, Perbill),
+}
+
+/// Roles an account can hold.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Role {
+    /// Settles bets. Settlers are bonded and tracked separately in `Settlers`.
+    Settler,
+    /// Can pause operations.
+    Guardian,
+    /// Can change fees and bet limits.
+    ParameterManager,
+    /// Can change the oracle endpoint and keys.
+    OracleManager,
+    /// Can change where treasury fees are paid.
+    TreasuryManager,
+}
+
+/// How a role is managed.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RoleConfig {
+    /// The largest number of accounts that may hold the role.
+    pub max_members: u32,
+    /// Holders of this role may grant and revoke the role, besides the admin origin.
+    pub manager: Option<Role>,
+}
+
+/// On-chain performance record of a settler.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct SettlerStats<BlockNumber> {
+    /// The number of bets this settler has settled.
+    pub settled: u32,
+    /// The sum of blocks between placement and settlement over all settled bets.
+    pub total_latency: BlockNumber,
+    /// The number of the settler's settlements that were disputed.
+    pub disputes: u32,
+}
+
+impl<BlockNumber: sp_runtime::traits::AtLeast32BitUnsigned + Copy> SettlerStats<BlockNumber> {
+    /// The average number of blocks a bet waited for this settler, if it settled any.
+    pub fn average_latency(&self) -> Option<BlockNumber> {
+        if self.settled == 0 {
+            None
+        } else {
+            Some(self.total_latency / self.settled.into())
+        }
+    }
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as Admin {
-        Settlers get (fn settlers) config(): Vec<T::AccountId>
+        Settlers get (fn settlers) config(): Vec<T::AccountId>;
+        /// The bond each settler currently has reserved.
+        Bonds get(fn bond): map hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+        /// Performance counters for each settler.
+        Performance get(fn settler_stats): map hasher(blake2_128_concat) T::AccountId => SettlerStats<T::BlockNumber>;
+        /// The settlements each settler made later than the settlement deadline in the current era.
+        MissedSettlements get(fn missed_settlements): map hasher(blake2_128_concat) T::AccountId => u32;
+        /// The block each scheduled settler removal takes effect.
+        PendingRemovals get(fn pending_removal): map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+        /// The settlers whose scheduled removal takes effect at a block.
+        RemovalsAt get(fn removals_at): map hasher(twox_64_concat) T::BlockNumber => Vec<T::AccountId>;
+        /// The smallest bet accepted, in base units of the bet currency. Never zero.
+        MinBet get(fn min_bet): u128 = 1;
+        /// The largest bet accepted, in base units of the bet currency.
+        MaxBet get(fn max_bet): u128 = u128::max_value();
+        /// The largest share of a pool's free balance a single bet may stake.
+        MaxBetPoolRatio get(fn max_bet_pool_ratio): Perbill = Perbill::one();
+        /// The multiplier of the fee taken from every bet.
+        FeeMultiplier get(fn fee_multiplier): u128 = 10;
+        /// Overrides the runtime's `SystemDecimals` once changed through a parameter change.
+        SystemDecimals get(fn system_decimals): Option<u128>;
+        /// The id of the next parameter change.
+        NextParameterChangeId get(fn next_parameter_change_id): u32;
+        /// Parameter changes waiting for their delay to pass, with the block they take effect.
+        ScheduledParameterChanges get(fn scheduled_parameter_change):
+            map hasher(twox_64_concat) u32 => Option<(ParameterChange, T::BlockNumber)>;
+        /// The sorted holders of each role other than `Settler`.
+        RoleMembers: map hasher(twox_64_concat) Role => Vec<T::AccountId>;
+        /// Overrides of the default role configuration.
+        RoleConfigs: map hasher(twox_64_concat) Role => Option<RoleConfig>;
+        /// Operations that are currently paused.
+        Paused get(fn is_paused): map hasher(twox_64_concat) Operation => bool;
+        /// The storage layout in use. New chains start at the latest one.
+        StorageVersion build(|_| Releases::V2): Releases;
+    }
+    add_extra_genesis {
+        config(roles): Vec<(Role, T::AccountId)>;
+        build(|config: &GenesisConfig<T>| {
+            for (role, who) in &config.roles {
+                assert!(*role != Role::Settler, "settlers are configured through `settlers`");
+                RoleMembers::<T>::mutate(role, |members| {
+                    if let Err(pos) = members.binary_search(who) {
+                        members.insert(pos, who.clone());
+                    }
+                });
+            }
+            // Genesis settlers are bonded like any other.
+            let bond = T::SettlerBond::get();
+            for who in &config.settlers {
+                T::Currency::reserve(who, bond).expect("genesis settlers must be able to reserve the settler bond");
+                Bonds::<T>::insert(who, bond);
+            }
+            T::SettlersChanged::set_members_sorted(&config.settlers, &[]);
+        });
     }
 }
 
 decl_event!(
     pub enum Event<T> where 
         AccountId = <T as system::Trait>::AccountId,
+        BlockNumber = <T as system::Trait>::BlockNumber,
+        Balance = BalanceOf<T>,
         {
             SettlerAdded(AccountId),
             SettlerRemoved(AccountId),
+            /// A settler's removal was scheduled. \[settler, effective_block\]
+            SettlerRemovalScheduled(AccountId, BlockNumber),
+            SettlerRemovalCancelled(AccountId),
+            /// A settler's bond was slashed into the pool. \[settler, amount\]
+            SettlerSlashed(AccountId, Balance),
+            /// A settler missed too many settlements in an era and was slashed. \[settler, missed\]
+            MissedSettlementsPenalised(AccountId, u32),
+            /// A settlement made by a settler was disputed. \[settler\]
+            SettlementDisputed(AccountId),
+            /// A settler was dropped for poor performance. \[settler\]
+            SettlerRotatedOut(AccountId),
+            /// A parameter change was scheduled. \[id, change, effective_block\]
+            ParameterChangeScheduled(u32, ParameterChange, BlockNumber),
+            /// A scheduled parameter change was cancelled. \[id\]
+            ParameterChangeCancelled(u32),
+            /// A parameter change took effect. \[id, change\]
+            ParameterChanged(u32, ParameterChange),
+            /// An account was granted a role. \[role, who\]
+            RoleGranted(Role, AccountId),
+            /// A role was revoked from an account. \[role, who\]
+            RoleRevoked(Role, AccountId),
+            /// A role's configuration was changed. \[role, max_members, manager\]
+            RoleConfigUpdated(Role, u32, Option<Role>),
+            /// An operation was paused. \[operation\]
+            OperationPaused(Operation),
+            /// An operation was resumed. \[operation\]
+            OperationUnpaused(Operation),
         }
     );
 
@@ -46,7 +260,32 @@ decl_error! {
         AlreadySetter,
         NotSettler,
         SettlerLimit,
-        LastSettler
+        LastSettler,
+        /// The minimum bet is zero or larger than the maximum bet.
+        InvalidBetLimits,
+        /// The fee multiplier is larger than `MAX_FEE_MULTIPLIER`.
+        InvalidFeeMultiplier,
+        /// The system decimals are larger than `MAX_SYSTEM_DECIMALS`.
+        InvalidSystemDecimals,
+        /// The maximum number of settlers is below the current number of settlers.
+        InvalidMaxSettlers,
+        /// The account already holds the role.
+        AlreadyHasRole,
+        /// The account doesn't hold the required role.
+        MissingRole,
+        /// The role already has its maximum number of members.
+        RoleLimit,
+        /// Settlers are managed with `add_setter` and `remove_settler`, and their limit with a
+        /// parameter change.
+        SettlerRole,
+        RemovalAlreadyScheduled,
+        NoRemovalScheduled,
+        /// The account can't reserve the settler bond.
+        InsufficientBond,
+        /// The scheduler refused the parameter change.
+        SchedulingFailed,
+        /// No parameter change with this id is scheduled.
+        UnknownParameterChange,
     }
 }
 
@@ -58,19 +297,215 @@ decl_module! {
 
         const MaxSettlers: u32 = T::MaxSettlers::get();
 
+        const RemovalDelay: T::BlockNumber = T::RemovalDelay::get();
+
+        const SettlerBond: BalanceOf<T> = T::SettlerBond::get();
+
+        const ParameterChangeDelay: T::BlockNumber = T::ParameterChangeDelay::get();
+
+        fn on_runtime_upgrade() -> Weight {
+            #[cfg(feature = "try-runtime")]
+            let snapshot = migrations::pre_migrate::<T>();
+            let weight = migrations::migrate::<T>();
+            #[cfg(feature = "try-runtime")]
+            {
+                if let Ok(snapshot) = snapshot {
+                    migrations::post_migrate::<T>(snapshot).expect("admin migration checks failed");
+                }
+            }
+            weight
+        }
+
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let mut weight = 0;
+            // An era length of zero disables eras.
+            let era_length = T::EraLength::get();
+            if !era_length.is_zero() && (now % era_length).is_zero() {
+                weight = Self::end_era();
+            }
+
+            let due = RemovalsAt::<T>::take(now);
+            weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+            for who in due {
+                PendingRemovals::<T>::remove(&who);
+                if Self::try_remove_settler(&who).is_ok() {
+                    Self::deposit_event(RawEvent::SettlerRemoved(who));
+                }
+                weight = weight.saturating_add(T::DbWeight::get().reads_writes(2, 4));
+            }
+            weight
+        }
+
         #[weight = 0]
-        fn add_setter(origin, who: T::AccountId) {
-            Self::ensure_settler(origin)?;
-            Self::try_add_settler(&who)?;
+        pub fn add_setter(origin, who: T::AccountId) {
+            Self::ensure_role_manager(origin, Role::Settler)?;
+            let bond = T::SettlerBond::get();
+            T::Currency::reserve(&who, bond).map_err(|_| Error::<T>::InsufficientBond)?;
+            if let Err(e) = Self::try_add_settler(&who) {
+                T::Currency::unreserve(&who, bond);
+                return Err(e);
+            }
+            Bonds::<T>::insert(&who, bond);
+            Performance::<T>::remove(&who);
 			Self::deposit_event(RawEvent::SettlerAdded(who));
         }
 
         #[weight = 0]
-        fn remove_settler(origin, who: T::AccountId) {
-            Self::ensure_settler(origin)?;
+        pub fn remove_settler(origin, who: T::AccountId) {
+            Self::ensure_role_manager(origin, Role::Settler)?;
             Self::try_remove_settler(&who)?;
+            Self::drop_pending_removal(&who);
 			Self::deposit_event(RawEvent::SettlerRemoved(who));
         }
+
+        /// Slash up to `amount` of a settler's bond into the pool, e.g. once fraud is proven.
+        #[weight = 0]
+        pub fn slash_settler(origin, who: T::AccountId, amount: BalanceOf<T>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(Self::is_settler(&who), Error::<T>::NotSettler);
+            Self::slash_bond(&who, amount);
+        }
+
+        /// Record that one of `who`'s settlements was disputed.
+        #[weight = 0]
+        pub fn dispute_settlement(origin, who: T::AccountId) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(Self::is_settler(&who), Error::<T>::NotSettler);
+            Performance::<T>::mutate(&who, |stats| stats.disputes = stats.disputes.saturating_add(1));
+            Self::deposit_event(RawEvent::SettlementDisputed(who));
+        }
+
+        /// Schedule removing a settler after `RemovalDelay` blocks, giving it time to finish
+        /// its settlements. The removal can be cancelled until then.
+        #[weight = 0]
+        pub fn schedule_settler_removal(origin, who: T::AccountId) {
+            Self::ensure_role_manager(origin, Role::Settler)?;
+            ensure!(Self::is_settler(&who), Error::<T>::NotSettler);
+            ensure!(Self::pending_removal(&who).is_none(), Error::<T>::RemovalAlreadyScheduled);
+            let at = <system::Module<T>>::block_number() + T::RemovalDelay::get();
+            PendingRemovals::<T>::insert(&who, at);
+            RemovalsAt::<T>::mutate(at, |removals| removals.push(who.clone()));
+            Self::deposit_event(RawEvent::SettlerRemovalScheduled(who, at));
+        }
+
+        #[weight = 0]
+        pub fn cancel_settler_removal(origin, who: T::AccountId) {
+            Self::ensure_role_manager(origin, Role::Settler)?;
+            ensure!(Self::pending_removal(&who).is_some(), Error::<T>::NoRemovalScheduled);
+            Self::drop_pending_removal(&who);
+            Self::deposit_event(RawEvent::SettlerRemovalCancelled(who));
+        }
+
+        /// Schedule `change` to take effect after `ParameterChangeDelay` blocks. Needs root or
+        /// a `ParameterManager`.
+        #[weight = 0]
+        pub fn schedule_parameter_change(origin, change: ParameterChange) {
+            Self::ensure_root_or_role(origin, Role::ParameterManager)?;
+            match change {
+                ParameterChange::FeeMultiplier(multiplier) =>
+                    ensure!(multiplier <= MAX_FEE_MULTIPLIER, Error::<T>::InvalidFeeMultiplier),
+                ParameterChange::SystemDecimals(decimals) =>
+                    ensure!(decimals <= MAX_SYSTEM_DECIMALS, Error::<T>::InvalidSystemDecimals),
+                ParameterChange::BetLimits(min_bet, max_bet, _) =>
+                    ensure!(min_bet > 0 && min_bet <= max_bet, Error::<T>::InvalidBetLimits),
+                ParameterChange::MaxSettlers(max_members) => ensure!(
+                    max_members as usize >= Self::settlers().len(),
+                    Error::<T>::InvalidMaxSettlers
+                ),
+            }
+            let id = Self::next_parameter_change_id();
+            let at = <system::Module<T>>::block_number() + T::ParameterChangeDelay::get();
+            T::Scheduler::schedule_named(
+                (PARAMETER_CHANGE_ID, id).encode(),
+                DispatchTime::At(at),
+                None,
+                HARD_DEADLINE,
+                system::RawOrigin::Root.into(),
+                Call::enact_parameter_change(id).into(),
+            ).map_err(|_| Error::<T>::SchedulingFailed)?;
+            NextParameterChangeId::put(id.wrapping_add(1));
+            ScheduledParameterChanges::<T>::insert(id, (change.clone(), at));
+            Self::deposit_event(RawEvent::ParameterChangeScheduled(id, change, at));
+        }
+
+        /// Cancel a parameter change before it takes effect.
+        #[weight = 0]
+        pub fn cancel_parameter_change(origin, id: u32) {
+            Self::ensure_root_or_role(origin, Role::ParameterManager)?;
+            ensure!(ScheduledParameterChanges::<T>::contains_key(id), Error::<T>::UnknownParameterChange);
+            T::Scheduler::cancel_named((PARAMETER_CHANGE_ID, id).encode())
+                .map_err(|_| Error::<T>::UnknownParameterChange)?;
+            ScheduledParameterChanges::<T>::remove(id);
+            Self::deposit_event(RawEvent::ParameterChangeCancelled(id));
+        }
+
+        /// Apply a scheduled parameter change. Dispatched by the scheduler.
+        #[weight = 0]
+        pub fn enact_parameter_change(origin, id: u32) {
+            ensure_root(origin)?;
+            let (change, _) = ScheduledParameterChanges::<T>::take(id)
+                .ok_or(Error::<T>::UnknownParameterChange)?;
+            Self::apply_parameter_change(&change);
+            Self::deposit_event(RawEvent::ParameterChanged(id, change));
+        }
+
+        /// Grant `role` to `who`. Needs the admin origin or the role's manager role.
+        #[weight = 0]
+        pub fn grant_role(origin, role: Role, who: T::AccountId) {
+            Self::ensure_role_manager(origin, role)?;
+            ensure!(role != Role::Settler, Error::<T>::SettlerRole);
+            let max_members = Self::role_config(role).max_members;
+            RoleMembers::<T>::try_mutate(role, |members| -> DispatchResult {
+                ensure!(members.len() < max_members as usize, Error::<T>::RoleLimit);
+                match members.binary_search(&who) {
+                    Ok(_) => Err(Error::<T>::AlreadyHasRole)?,
+                    Err(pos) => members.insert(pos, who.clone()),
+                }
+                Ok(())
+            })?;
+            Self::deposit_event(RawEvent::RoleGranted(role, who));
+        }
+
+        #[weight = 0]
+        pub fn revoke_role(origin, role: Role, who: T::AccountId) {
+            Self::ensure_role_manager(origin, role)?;
+            ensure!(role != Role::Settler, Error::<T>::SettlerRole);
+            RoleMembers::<T>::try_mutate(role, |members| -> DispatchResult {
+                match members.binary_search(&who) {
+                    Ok(pos) => members.remove(pos),
+                    Err(_) => Err(Error::<T>::MissingRole)?,
+                };
+                Ok(())
+            })?;
+            Self::deposit_event(RawEvent::RoleRevoked(role, who));
+        }
+
+        /// Set the maximum size of `role` and the role whose holders may manage it. The settler
+        /// limit is a parameter and changes through `schedule_parameter_change`.
+        #[weight = 0]
+        pub fn set_role_config(origin, role: Role, max_members: u32, manager: Option<Role>) {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                role != Role::Settler || max_members == Self::role_config(role).max_members,
+                Error::<T>::SettlerRole
+            );
+            RoleConfigs::insert(role, RoleConfig { max_members, manager });
+            Self::deposit_event(RawEvent::RoleConfigUpdated(role, max_members, manager));
+        }
+
+        /// Pause `operation`. Guardians can pause, but only root can resume.
+        #[weight = 0]
+        pub fn pause(origin, operation: Operation) {
+            Self::ensure_root_or_role(origin, Role::Guardian)?;
+            Self::trip(operation);
+        }
+
+        #[weight = 0]
+        pub fn unpause(origin, operation: Operation) {
+            ensure_root(origin)?;
+            Paused::remove(operation);
+            Self::deposit_event(RawEvent::OperationUnpaused(operation));
+        }
     }
 }
 
@@ -85,23 +520,158 @@ impl<T: Trait> Module<T> {
         ensure!(Self::is_settler(&caller), Error::<T>::NotSettler);
         Ok(caller)
     }
+
+    /// The configuration of `role`, falling back to the trait defaults.
+    pub fn role_config(role: Role) -> RoleConfig {
+        RoleConfigs::get(role).unwrap_or_else(|| RoleConfig {
+            max_members: match role {
+                Role::Settler => T::MaxSettlers::get(),
+                _ => T::MaxRoleMembers::get(),
+            },
+            manager: None,
+        })
+    }
+
+    /// The sorted holders of `role`.
+    pub fn role_members(role: Role) -> Vec<T::AccountId> {
+        match role {
+            Role::Settler => Self::settlers(),
+            _ => RoleMembers::<T>::get(role),
+        }
+    }
+
+    pub fn has_role(who: &T::AccountId, role: Role) -> bool {
+        Self::role_members(role).binary_search(who).is_ok()
+    }
+
+    /// Ensure `origin` is signed by a holder of `role` and return the holder.
+    pub fn ensure_role(origin: T::Origin, role: Role) -> Result<T::AccountId, DispatchError> {
+        let caller = ensure_signed(origin)?;
+        ensure!(Self::has_role(&caller, role), Error::<T>::MissingRole);
+        Ok(caller)
+    }
+
+    pub fn ensure_root_or_role(origin: T::Origin, role: Role) -> DispatchResult {
+        match ensure_root(origin.clone()) {
+            Ok(_) => Ok(()),
+            Err(_) => Self::ensure_role(origin, role).map(|_| ()),
+        }
+    }
+
+    /// Ensure `origin` may manage `role`: the admin origin or a holder of the role's manager role.
+    fn ensure_role_manager(origin: T::Origin, role: Role) -> DispatchResult {
+        match T::AdminOrigin::try_origin(origin) {
+            Ok(_) => Ok(()),
+            Err(origin) => match Self::role_config(role).manager {
+                Some(manager) => Self::ensure_role(origin, manager).map(|_| ()),
+                None => Err(BadOrigin.into()),
+            },
+        }
+    }
+    /// Apply `change` immediately, e.g. from the genesis config of pallet_chance.
+    pub fn apply_parameter_change(change: &ParameterChange) {
+        match *change {
+            ParameterChange::FeeMultiplier(multiplier) => FeeMultiplier::put(multiplier),
+            ParameterChange::SystemDecimals(decimals) => SystemDecimals::put(decimals),
+            ParameterChange::MaxSettlers(max_members) => {
+                let manager = Self::role_config(Role::Settler).manager;
+                RoleConfigs::insert(Role::Settler, RoleConfig { max_members, manager });
+            }
+            ParameterChange::BetLimits(min_bet, max_bet, max_bet_pool_ratio) => {
+                MinBet::put(min_bet);
+                MaxBet::put(max_bet);
+                MaxBetPoolRatio::put(max_bet_pool_ratio);
+            }
+        }
+    }
+
+    /// Record that `who` settled a bet `latency` blocks after it was placed.
+    pub fn note_settlement(who: &T::AccountId, latency: T::BlockNumber) {
+        Performance::<T>::mutate(who, |stats| {
+            stats.settled = stats.settled.saturating_add(1);
+            stats.total_latency = stats.total_latency.saturating_add(latency);
+        });
+    }
+
+    /// Record that `who` settled a bet after its deadline.
+    pub fn note_missed_settlement(who: &T::AccountId) {
+        MissedSettlements::<T>::mutate(who, |missed| *missed = missed.saturating_add(1));
+    }
+
+    /// Slash the settlers that missed too many settlements, reset the counts and rotate out
+    /// underperformers. Returns the weight used.
+    fn end_era() -> Weight {
+        let mut weight = 0;
+        for (settler, missed) in MissedSettlements::<T>::drain() {
+            weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+            if missed > T::MaxMissedSettlements::get() {
+                let amount = T::MissedSettlementSlash::get() * Self::bond(&settler);
+                Self::slash_bond(&settler, amount);
+                Self::deposit_event(RawEvent::MissedSettlementsPenalised(settler, missed));
+                weight = weight.saturating_add(T::DbWeight::get().reads_writes(2, 2));
+            }
+        }
+        weight.saturating_add(Self::rotate_underperformers())
+    }
+
+    /// Drop settlers that are too slow or disputed too often, keeping at least one. Returns the
+    /// weight used.
+    fn rotate_underperformers() -> Weight {
+        let settlers = Self::settlers();
+        let weight = T::DbWeight::get().reads_writes(1 + 2 * settlers.len() as u64, settlers.len() as u64);
+        for settler in settlers {
+            let stats = Self::settler_stats(&settler);
+            let too_slow = stats.average_latency().map_or(false, |latency| latency > T::MaxAverageLatency::get());
+            if (too_slow || stats.disputes > T::MaxDisputes::get()) && Self::try_remove_settler(&settler).is_ok() {
+                Self::deposit_event(RawEvent::SettlerRotatedOut(settler));
+            }
+        }
+        weight
+    }
+
+    /// Forget the scheduled removal of `who`, if any.
+    fn drop_pending_removal(who: &T::AccountId) {
+        if let Some(at) = PendingRemovals::<T>::take(who) {
+            RemovalsAt::<T>::mutate(at, |removals| removals.retain(|removal| removal != who));
+        }
+    }
+
+    fn slash_bond(who: &T::AccountId, amount: BalanceOf<T>) {
+        let amount = amount.min(Self::bond(who));
+        if amount.is_zero() {
+            return;
+        }
+        let (imbalance, _) = T::Currency::slash_reserved(who, amount);
+        T::Slash::on_unbalanced(imbalance);
+        Bonds::<T>::mutate(who, |bond| *bond = bond.saturating_sub(amount));
+        Self::deposit_event(RawEvent::SettlerSlashed(who.clone(), amount));
+    }
+
+    /// Pause `operation` without an origin check, e.g. when a circuit breaker trips.
+    pub fn trip(operation: Operation) {
+        if !Self::is_paused(operation) {
+            Paused::insert(operation, true);
+            Self::deposit_event(RawEvent::OperationPaused(operation));
+        }
+    }
     pub fn try_add_settler(who: &T::AccountId) -> DispatchResult {
-        Settlers::<T>::try_mutate(|settlers| -> DispatchResult {
-            if settlers.len() < T::MaxSettlers::get() as usize {
+        let settlers = Settlers::<T>::try_mutate(|settlers| -> Result<Vec<T::AccountId>, DispatchError> {
+            if settlers.len() < Self::role_config(Role::Settler).max_members as usize {
                 match settlers.binary_search(&who) {
                     Ok(_) => Err(Error::<T>::AlreadySetter)?,
                     Err(pos) => settlers.insert(pos, who.clone()),
                 }
-                Ok(())
+                Ok(settlers.clone())
             } else {
                 Err(Error::<T>::SettlerLimit)?
             }
         })?;
+        T::SettlersChanged::change_members_sorted(&[who.clone()], &[], &settlers);
         Ok(())
     }
 
     fn try_remove_settler(who: &T::AccountId) -> DispatchResult {
-		Settlers::<T>::try_mutate(|settlers| -> DispatchResult {
+		let settlers = Settlers::<T>::try_mutate(|settlers| -> Result<Vec<T::AccountId>, DispatchError> {
 			if settlers.len() == 1 as usize {
 				Err(Error::<T>::LastSettler)?
 			} else {
@@ -109,9 +679,11 @@ impl<T: Trait> Module<T> {
 					Ok(pos) => settlers.remove(pos),
 					Err(_) => Err(Error::<T>::NotSettler)?,
 				};
-				Ok(())
+				Ok(settlers.clone())
 			}
 		})?;
+		T::Currency::unreserve(who, Bonds::<T>::take(who));
+		T::SettlersChanged::change_members_sorted(&[], &[who.clone()], &settlers);
 		Ok(())
 	}
 }
\ No newline at end of file
@@ -0,0 +1,84 @@
+//! Storage migrations.
+//!
+//! `V1` kept `Settlers` in genesis order, while membership checks binary search it, and didn't bond
+//! settlers. `V2` keeps it sorted and free of duplicates and reserves `SettlerBond` from every
+//! settler. Settlers that can't reserve the bond are dropped, so every settler has something to
+//! slash. The resulting set is reported to `SettlersChanged`.
+//!
+//! With the `try-runtime` feature, `pre_migrate` and `post_migrate` check the state around the
+//! migration on every runtime upgrade. The tests run them too.
+
+use crate::*;
+
+/// The storage layouts of this pallet.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+    /// Settlers in genesis order.
+    V1,
+    /// Sorted settlers.
+    V2,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1
+    }
+}
+
+/// Sort and bond the `V1` settlers.
+pub fn migrate<T: Trait>() -> Weight {
+    if StorageVersion::get() != Releases::V1 {
+        return 0;
+    }
+    let mut settlers = Settlers::<T>::get();
+    settlers.sort();
+    settlers.dedup();
+    let count = settlers.len() as Weight;
+    let bond = T::SettlerBond::get();
+    settlers.retain(|who| {
+        if !Module::<T>::bond(who).is_zero() {
+            return true;
+        }
+        if T::Currency::reserve(who, bond).is_err() {
+            Module::<T>::deposit_event(RawEvent::SettlerRemoved(who.clone()));
+            return false;
+        }
+        Bonds::<T>::insert(who, bond);
+        true
+    });
+    T::SettlersChanged::set_members_sorted(&settlers, &[]);
+    Settlers::<T>::put(settlers);
+    StorageVersion::put(Releases::V2);
+    T::DbWeight::get().reads_writes(2 + 2 * count, 2 + 2 * count)
+}
+
+/// Record the `V1` settlers before migrating.
+#[cfg(any(test, feature = "try-runtime"))]
+pub fn pre_migrate<T: Trait>() -> Result<Vec<T::AccountId>, &'static str> {
+    if StorageVersion::get() != Releases::V1 {
+        return Err("admin storage is already migrated");
+    }
+    Ok(Settlers::<T>::get())
+}
+
+/// Check the settlers are sorted, bonded, and that only unbondable ones were dropped.
+#[cfg(any(test, feature = "try-runtime"))]
+pub fn post_migrate<T: Trait>(mut settlers: Vec<T::AccountId>) -> Result<(), &'static str> {
+    if StorageVersion::get() != Releases::V2 {
+        return Err("admin storage version wasn't bumped");
+    }
+    settlers.sort();
+    settlers.dedup();
+    let migrated = Settlers::<T>::get();
+    if migrated.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err("settlers aren't sorted");
+    }
+    for who in settlers {
+        let kept = migrated.binary_search(&who).is_ok();
+        let bonded = !Module::<T>::bond(&who).is_zero();
+        if kept != bonded {
+            return Err("a settler was dropped or kept without a bond");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use crate::{Module, Trait};
+use crate as admin;
+use sp_core::H256;
+use frame_support::{impl_outer_dispatch, impl_outer_origin, parameter_types, weights::Weight};
+use sp_runtime::{
+    traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
+};
+use frame_system as system;
+
+impl_outer_origin! {
+    pub enum Origin for Test {}
+}
+
+impl_outer_dispatch! {
+    pub enum Call for Test where origin: Origin {
+        system::System,
+        admin::Admin,
+    }
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 1024;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+pub type Balances = pallet_balances::Module<Test>;
+pub type System = frame_system::Module<Test>;
+pub type Scheduler = pallet_scheduler::Module<Test>;
+pub type Admin = Module<Test>;
+
+impl system::Trait for Test {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = u64;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type MaximumBlockWeight = MaximumBlockWeight;
+    type DbWeight = ();
+    type BlockExecutionWeight = ();
+    type ExtrinsicBaseWeight = ();
+    type MaximumExtrinsicWeight = MaximumBlockWeight;
+    type MaximumBlockLength = MaximumBlockLength;
+    type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+    type AccountData = pallet_balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+    type Balance = u64;
+    type Event = ();
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+    type MaxLocks = ();
+}
+
+parameter_types! {
+    pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * MaximumBlockWeight::get();
+    pub const MaxScheduledPerBlock: u32 = 10;
+}
+
+impl pallet_scheduler::Trait for Test {
+    type Event = ();
+    type Origin = Origin;
+    type PalletsOrigin = OriginCaller;
+    type Call = Call;
+    type MaximumWeight = MaximumSchedulerWeight;
+    type ScheduleOrigin = frame_system::EnsureRoot<u64>;
+    type MaxScheduledPerBlock = MaxScheduledPerBlock;
+    type WeightInfo = ();
+}
+
+pub const SETTLER_BOND: u64 = 100;
+
+parameter_types! {
+    pub const MaxSettlers: u32 = 10;
+    pub const MaxRoleMembers: u32 = 5;
+    pub const RemovalDelay: u64 = 5;
+    pub const SettlerBond: u64 = SETTLER_BOND;
+    pub const EraLength: u64 = 20;
+    pub const MaxMissedSettlements: u32 = 1;
+    pub const MissedSettlementSlash: Perbill = Perbill::from_percent(10);
+    pub const MaxAverageLatency: u64 = 10;
+    pub const MaxDisputes: u32 = 2;
+    pub const ParameterChangeDelay: u64 = 3;
+}
+
+impl Trait for Test {
+    type Event = ();
+    type MaxSettlers = MaxSettlers;
+    type MaxRoleMembers = MaxRoleMembers;
+    type AdminOrigin = frame_system::EnsureRoot<u64>;
+    type RemovalDelay = RemovalDelay;
+    type SettlersChanged = ();
+    type Currency = Balances;
+    type SettlerBond = SettlerBond;
+    type Slash = ();
+    type EraLength = EraLength;
+    type MaxMissedSettlements = MaxMissedSettlements;
+    type MissedSettlementSlash = MissedSettlementSlash;
+    type MaxAverageLatency = MaxAverageLatency;
+    type MaxDisputes = MaxDisputes;
+    type Proposal = Call;
+    type Scheduler = Scheduler;
+    type PalletsOrigin = OriginCaller;
+    type ParameterChangeDelay = ParameterChangeDelay;
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, 1000), (2, 1000), (3, 1000), (4, 1000)],
+    }.assimilate_storage(&mut t).unwrap();
+    crate::GenesisConfig::<Test> {
+        settlers: vec![1, 2],
+        ..Default::default()
+    }.assimilate_storage(&mut t).unwrap();
+    let mut ext = sp_io::TestExternalities::new(t);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
@@ -0,0 +1,49 @@
+use crate::{migrations::{self, Releases}, mock::*, Error, ParameterChange, Settlers, StorageVersion};
+use frame_support::{assert_noop, assert_ok, StorageValue};
+
+#[test]
+fn test_genesis_starts_at_the_latest_storage_version() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(StorageVersion::get(), Releases::V2);
+        assert_eq!(Admin::settlers(), vec![1, 2]);
+        assert_eq!(Admin::bond(1), SETTLER_BOND);
+        // Nothing to migrate on a new chain.
+        assert!(migrations::pre_migrate::<Test>().is_err());
+        assert_eq!(migrations::migrate::<Test>(), 0);
+    });
+}
+
+#[test]
+fn test_migrating_v1_sorts_dedups_and_bonds_settlers() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::put(Releases::V1);
+        // 5 has nothing to reserve the bond from
+        Settlers::<Test>::put(vec![4, 1, 5, 3, 1]);
+
+        let snapshot = migrations::pre_migrate::<Test>().unwrap();
+        migrations::migrate::<Test>();
+        assert_ok!(migrations::post_migrate::<Test>(snapshot));
+
+        assert_eq!(Admin::settlers(), vec![1, 3, 4]);
+        assert_eq!(StorageVersion::get(), Releases::V2);
+        // the genesis settler isn't bonded twice
+        assert_eq!(Balances::reserved_balance(1), SETTLER_BOND);
+        assert_eq!((Admin::bond(3), Balances::reserved_balance(3)), (SETTLER_BOND, SETTLER_BOND));
+        assert_eq!((Admin::bond(4), Balances::reserved_balance(4)), (SETTLER_BOND, SETTLER_BOND));
+        assert_eq!(Admin::bond(5), 0);
+        // Migrating again is a no-op.
+        assert_eq!(migrations::migrate::<Test>(), 0);
+        assert_eq!(Admin::settlers(), vec![1, 3, 4]);
+    });
+}
+
+#[test]
+fn test_max_settlers_cannot_drop_below_the_settler_count() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Admin::schedule_parameter_change(Origin::root(), ParameterChange::MaxSettlers(1)),
+            Error::<Test>::InvalidMaxSettlers
+        );
+        assert_ok!(Admin::schedule_parameter_change(Origin::root(), ParameterChange::MaxSettlers(2)));
+    });
+}
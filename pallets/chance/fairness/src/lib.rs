@@ -0,0 +1,166 @@
+//! Provably-fair outcome derivation for pallet_chance and the games built on it.
+//!
+//! Every bet, lottery round and prediction round is assigned the index of an oracle seed on
+//! chain before its outcome can be known. The oracle's seeds come from a published commit–reveal
+//! hash chain, and it reveals the seed at that index together with its sr25519 signature of the
+//! subject, the index and the seed. The chain only accepts a seed signed by the oracle key it
+//! holds, so a settler can neither pick a seed nor submit one meant for another subject.
+//!
+//! A bet's outcome is the low bit of the BLAKE2-256 hash of the seed, the hash of the block the
+//! bet's cancellation window closed in and the bet id. That block is only built once the bet can
+//! no longer be cancelled, so knowing the seed early doesn't tell a better whether to cancel.
+//! Lottery tickets and prediction outcomes are drawn with `draw` and `draw_distinct` from the
+//! round's seed mixed with the hash of the block the round closed in by `round_seed`. The same
+//! functions are used on chain and by offline verifiers.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_core::sr25519;
+use sp_io::hashing::blake2_256;
+use sp_std::prelude::*;
+
+/// The prefix of every message the oracle signs.
+pub const SIGNING_CONTEXT: &[u8] = b"chance-oracle/seed";
+
+/// What an oracle seed decides.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Subject {
+	/// The outcome of a bet, by id.
+	Bet(u64),
+	/// The winning tickets of a lottery round.
+	Lottery(u32),
+	/// The winning outcome of a prediction round.
+	Prediction(u32),
+}
+
+impl Subject {
+	/// The name of the subject's kind in oracle requests.
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Subject::Bet(_) => "bet",
+			Subject::Lottery(_) => "lottery",
+			Subject::Prediction(_) => "prediction",
+		}
+	}
+
+	/// The subject named `kind` with id `id` in an oracle request.
+	pub fn from_request(kind: &str, id: u64) -> Option<Self> {
+		match kind {
+			"bet" => Some(Subject::Bet(id)),
+			"lottery" if id <= u32::max_value() as u64 => Some(Subject::Lottery(id as u32)),
+			"prediction" if id <= u32::max_value() as u64 => Some(Subject::Prediction(id as u32)),
+			_ => None,
+		}
+	}
+}
+
+/// A seed revealed by the oracle for one subject.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Reveal {
+	/// The position of the seed in the oracle's seed chains.
+	pub index: u64,
+	pub seed: Vec<u8>,
+	/// The oracle's sr25519 signature of `signed_message(subject, index, seed)`.
+	pub signature: Vec<u8>,
+}
+
+impl Reveal {
+	/// Whether `oracle` signed this seed for `subject`.
+	pub fn is_signed_by(&self, oracle: &sr25519::Public, subject: Subject) -> bool {
+		if self.signature.len() != 64 {
+			return false;
+		}
+		let mut signature = [0u8; 64];
+		signature.copy_from_slice(&self.signature);
+		sp_io::crypto::sr25519_verify(
+			&sr25519::Signature::from_raw(signature),
+			&signed_message(subject, self.index, &self.seed),
+			oracle,
+		)
+	}
+}
+
+/// The result of checking a bet's randomness.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Verdict {
+	Won,
+	Lost,
+	/// The oracle didn't sign the seed for this bet and index.
+	InvalidSignature,
+}
+
+/// The message the oracle signs when it reveals `seed` at `index` for `subject`.
+pub fn signed_message(subject: Subject, index: u64, seed: &[u8]) -> Vec<u8> {
+	(SIGNING_CONTEXT, subject, index, seed).encode()
+}
+
+/// The hash the outcome of bet `bet_id` is read from.
+pub fn roll(seed: &[u8], block_hash: &[u8], bet_id: u64) -> [u8; 32] {
+	let mut input = Vec::with_capacity(seed.len() + block_hash.len() + 8);
+	input.extend_from_slice(seed);
+	input.extend_from_slice(block_hash);
+	input.extend_from_slice(&bet_id.to_le_bytes());
+	blake2_256(&input)
+}
+
+/// Whether bet `bet_id` is won given `seed` and the hash of the block its cancellation window
+/// closed in.
+pub fn is_win(seed: &[u8], block_hash: &[u8], bet_id: u64) -> bool {
+	roll(seed, block_hash, bet_id)[0] & 1 == 1
+}
+
+/// Check that `oracle` signed `reveal` for bet `bet_id` and recompute the bet's outcome.
+pub fn verify(oracle: &sr25519::Public, reveal: &Reveal, block_hash: &[u8], bet_id: u64) -> Verdict {
+	if !reveal.is_signed_by(oracle, Subject::Bet(bet_id)) {
+		Verdict::InvalidSignature
+	} else if is_win(&reveal.seed, block_hash, bet_id) {
+		Verdict::Won
+	} else {
+		Verdict::Lost
+	}
+}
+
+/// The seed a round is drawn with: the BLAKE2-256 hash of the oracle's `seed` and the hash of the
+/// block the round closed in.
+pub fn round_seed(seed: &[u8], block_hash: &[u8]) -> [u8; 32] {
+	let mut input = Vec::with_capacity(seed.len() + block_hash.len());
+	input.extend_from_slice(seed);
+	input.extend_from_slice(block_hash);
+	blake2_256(&input)
+}
+
+/// The `round`th number below `bound` drawn from `seed` for `subject`. A zero `bound` is taken as
+/// one.
+pub fn draw(seed: &[u8], subject: Subject, round: u32, bound: u32) -> u32 {
+	let hash = blake2_256(&(seed, subject, round).encode());
+	let mut bytes = [0u8; 8];
+	bytes.copy_from_slice(&hash[..8]);
+	(u64::from_le_bytes(bytes) % u64::from(bound.max(1))) as u32
+}
+
+/// `count` distinct numbers below `bound` drawn from `seed` for `subject`, in draw order. At most
+/// `bound` numbers are drawn.
+pub fn draw_distinct(seed: &[u8], subject: Subject, count: u32, bound: u32) -> Vec<u32> {
+	let count = count.min(bound);
+	let mut picks: Vec<u32> = Vec::with_capacity(count as usize);
+	let mut taken: Vec<u32> = Vec::with_capacity(count as usize);
+	for round in 0..count {
+		// draw among the numbers not picked yet and skip over the picked ones
+		let mut pick = draw(seed, subject, round, bound - round);
+		for t in &taken {
+			if *t <= pick {
+				pick += 1;
+			}
+		}
+		let at = taken.binary_search(&pick).unwrap_or_else(|at| at);
+		taken.insert(at, pick);
+		picks.push(pick);
+	}
+	picks
+}
@@ -0,0 +1,130 @@
+//! RPC interface for the chance pallet.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_chance_runtime_api::{
+	BetId, BetOutcome, BetRecord, BetTotals, BetVerification, ChanceApi as ChanceRuntimeApi, FairnessRecord,
+	GamingStatus, ReferralReward, ReferralStats, Subject, Verdict,
+};
+
+#[rpc]
+pub trait ChanceApi<BlockHash, AccountId, CurrencyId, Balance, BlockNumber, Hash> {
+	#[rpc(name = "chance_betHistory")]
+	fn bet_history(&self, who: AccountId, at: Option<BlockHash>) -> Result<Vec<BetRecord<CurrencyId, Balance, BlockNumber>>>;
+
+	#[rpc(name = "chance_betTotals")]
+	fn bet_totals(&self, currency_id: CurrencyId, at: Option<BlockHash>) -> Result<BetTotals<Balance>>;
+
+	#[rpc(name = "chance_gamingStatus")]
+	fn gaming_status(
+		&self,
+		who: AccountId,
+		currency_id: CurrencyId,
+		at: Option<BlockHash>,
+	) -> Result<GamingStatus<Balance, BlockNumber>>;
+
+	#[rpc(name = "chance_referralStats")]
+	fn referral_stats(
+		&self,
+		who: AccountId,
+		currency_id: CurrencyId,
+		at: Option<BlockHash>,
+	) -> Result<ReferralStats<AccountId, Balance>>;
+
+	#[rpc(name = "chance_verifyBet")]
+	fn verify_bet(&self, bet_id: BetId, at: Option<BlockHash>) -> Result<Option<BetVerification<Hash>>>;
+
+	#[rpc(name = "chance_seedDue")]
+	fn seed_due(&self, subject: Subject, index: u64, at: Option<BlockHash>) -> Result<bool>;
+}
+
+/// A struct that implements the `ChanceApi`.
+pub struct Chance<C, B> {
+	client: Arc<C>,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> Chance<C, B> {
+	/// Create new `Chance` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(1),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, AccountId, CurrencyId, Balance, BlockNumber, Hash>
+	ChanceApi<<Block as BlockT>::Hash, AccountId, CurrencyId, Balance, BlockNumber, Hash> for Chance<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: ChanceRuntimeApi<Block, AccountId, CurrencyId, Balance, BlockNumber, Hash>,
+	AccountId: Codec,
+	CurrencyId: Codec,
+	Balance: Codec,
+	BlockNumber: Codec,
+	Hash: Codec,
+{
+	fn bet_history(
+		&self,
+		who: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<BetRecord<CurrencyId, Balance, BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.bet_history(&at, who).map_err(|e| runtime_error("Unable to query bet history.", e))
+	}
+
+	fn bet_totals(&self, currency_id: CurrencyId, at: Option<<Block as BlockT>::Hash>) -> Result<BetTotals<Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.bet_totals(&at, currency_id).map_err(|e| runtime_error("Unable to query bet totals.", e))
+	}
+
+	fn gaming_status(
+		&self,
+		who: AccountId,
+		currency_id: CurrencyId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<GamingStatus<Balance, BlockNumber>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.gaming_status(&at, who, currency_id).map_err(|e| runtime_error("Unable to query gaming status.", e))
+	}
+
+	fn referral_stats(
+		&self,
+		who: AccountId,
+		currency_id: CurrencyId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<ReferralStats<AccountId, Balance>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.referral_stats(&at, who, currency_id).map_err(|e| runtime_error("Unable to query referral stats.", e))
+	}
+
+	fn verify_bet(&self, bet_id: BetId, at: Option<<Block as BlockT>::Hash>) -> Result<Option<BetVerification<Hash>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.verify_bet(&at, bet_id).map_err(|e| runtime_error("Unable to verify bet.", e))
+	}
+
+	fn seed_due(&self, subject: Subject, index: u64, at: Option<<Block as BlockT>::Hash>) -> Result<bool> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		api.seed_due(&at, subject, index).map_err(|e| runtime_error("Unable to check the seed.", e))
+	}
+}
@@ -0,0 +1,41 @@
+//! Runtime API definition for querying bet history, per-currency totals, player limits,
+//! referral stats, the randomness bets were settled with, whether the oracle may reveal a seed,
+//! and the settlement backlog and pool liquidity monitored by the node.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+pub use pallet_chance::{
+    BetId, BetOutcome, BetRecord, BetTotals, BetVerification, FairnessRecord, GamingStatus, PoolStatus,
+    ReferralReward, ReferralStats, SettlementQueue, Subject, Verdict,
+};
+
+sp_api::decl_runtime_apis! {
+    pub trait ChanceApi<AccountId, CurrencyId, Balance, BlockNumber, Hash> where
+        AccountId: Codec,
+        CurrencyId: Codec,
+        Balance: Codec,
+        BlockNumber: Codec,
+        Hash: Codec,
+    {
+        /// The finished bets of `who`, oldest first.
+        fn bet_history(who: AccountId) -> Vec<BetRecord<CurrencyId, Balance, BlockNumber>>;
+        /// The running totals of bets placed in `currency_id`.
+        fn bet_totals(currency_id: CurrencyId) -> BetTotals<Balance>;
+        /// The responsible-gaming limits, usage and self-exclusion of `who` in `currency_id`.
+        fn gaming_status(who: AccountId, currency_id: CurrencyId) -> GamingStatus<Balance, BlockNumber>;
+        /// The referrer, referral count and rewards of `who` in `currency_id`.
+        fn referral_stats(who: AccountId, currency_id: CurrencyId) -> ReferralStats<AccountId, Balance>;
+        /// The randomness bet `bet_id` was settled with and the outcome recomputed from it, if it
+        /// was settled provably fairly and hasn't been pruned.
+        fn verify_bet(bet_id: BetId) -> Option<BetVerification<Hash>>;
+        /// Whether the seed at `index` is assigned to `subject` and its outcome can no longer be
+        /// changed, so the oracle may reveal it.
+        fn seed_due(subject: Subject, index: u64) -> bool;
+        /// The number of bets waiting for a settler and when the oldest was placed.
+        fn settlement_queue() -> SettlementQueue<BlockNumber>;
+        /// The reserves and issued LP units of the `currency_id` pool.
+        fn pool_status(currency_id: CurrencyId) -> PoolStatus<Balance>;
+    }
+}
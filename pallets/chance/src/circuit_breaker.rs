@@ -0,0 +1,40 @@
+//! Automatic pausing of the game when a pool loses too much too quickly.
+//!
+//! Every settlement adjusts the net losses of its pool for the current window of
+//! `CircuitBreakerWindow` blocks. Once they exceed `CircuitBreakerThreshold` of the pool's
+//! reserve at the start of the window, betting and settlement are paused in pallet_admin until
+//! root resumes them.
+
+use crate::*;
+use admin::Operation;
+use sp_runtime::traits::{Saturating, Zero};
+
+impl<T: Trait> Module<T> {
+	/// Record the pool's gain or loss from a settled bet and trip the breaker if needed.
+	pub(crate) fn note_settlement(currency_id: CurrencyIdOf<T>, wager: BalanceOf<T>, did_win: bool) {
+		let now = <system::Module<T>>::block_number();
+		let mut window = Self::loss_window(currency_id);
+		if window.opening_reserve.is_zero() || now >= window.start.saturating_add(T::CircuitBreakerWindow::get()) {
+			let reserve = <T as Trait>::Currency::free_balance(currency_id, &Self::account_id());
+			// the reserve is taken after this settlement, so put back what it paid out
+			let opening_reserve = if did_win { reserve.saturating_add(wager.mul(EVEN_MONEY.into())) } else { reserve };
+			window = LossWindow { start: now, opening_reserve, losses: Zero::zero() };
+		}
+
+		if did_win {
+			window.losses = window.losses.saturating_add(wager);
+		} else {
+			window.losses = window.losses.saturating_sub(wager);
+		}
+
+		let opening_reserve = TryInto::<u128>::try_into(window.opening_reserve).unwrap_or(u128::max_value());
+		let losses = TryInto::<u128>::try_into(window.losses).unwrap_or(u128::max_value());
+		if losses > T::CircuitBreakerThreshold::get() * opening_reserve {
+			debug::warn!("Circuit breaker tripped for pool {:?}", currency_id);
+			<admin::Module<T>>::trip(Operation::Bet);
+			<admin::Module<T>>::trip(Operation::Settle);
+			Self::deposit_event(RawEvent::CircuitBreakerTripped(currency_id, window.losses));
+		}
+		LossWindows::<T>::insert(currency_id, window);
+	}
+}
@@ -0,0 +1,44 @@
+//! Distribution of the fee taken from every bet.
+//!
+//! Only `amount - fee` of a bet is wagered, so the whole fee lands in the pool. The treasury's
+//! cut is paid out as soon as the bet is placed, the settler's cut is paid to whichever settler
+//! settles the bet, the better's referrer is credited its cut, and whatever is left stays in the
+//! pool for the LPs.
+
+use crate::*;
+use sp_runtime::traits::Zero;
+
+impl<T: Trait> Module<T> {
+	/// Pay the treasury and the referrer of `better` their cuts of `fee` and return the settler's
+	/// cut, which stays in the pool until the bet is settled.
+	pub(crate) fn distribute_bet_fee(better: &T::AccountId, currency_id: CurrencyIdOf<T>, fee: u128) -> Result<BalanceOf<T>, dispatch::DispatchError> {
+		let treasury_fee = T::TreasuryCut::get() * fee;
+		let settler_fee = T::SettlerCut::get() * fee;
+		let lp_fee = fee.saturating_sub(treasury_fee).saturating_sub(settler_fee);
+
+		let treasury_fee = Self::u128_to_balance(treasury_fee);
+		if !treasury_fee.is_zero() {
+			let treasury = Self::treasury_account();
+			<T as Trait>::Currency::transfer(currency_id, &Self::account_id(), &treasury, treasury_fee)?;
+			Self::deposit_event(RawEvent::TreasuryFeePaid(treasury, currency_id, treasury_fee));
+		}
+		let lp_fee = lp_fee.saturating_sub(Self::pay_referral_reward(better, currency_id, fee, lp_fee)?);
+		Self::deposit_event(RawEvent::LiquidityFeeRetained(currency_id, Self::u128_to_balance(lp_fee)));
+
+		Ok(Self::u128_to_balance(settler_fee))
+	}
+
+	/// The account treasury fees are paid to.
+	pub fn treasury_account() -> T::AccountId {
+		Self::treasury_override().unwrap_or_else(T::TreasuryAccount::get)
+	}
+
+	/// Pay `settler` the fee share that was held back when `bet` was placed.
+	pub(crate) fn pay_settler_fee(settler: &T::AccountId, bet: &PendingBetOf<T>) -> dispatch::DispatchResult {
+		if !bet.settler_fee.is_zero() {
+			<T as Trait>::Currency::transfer(bet.currency_id, &Self::account_id(), settler, bet.settler_fee)?;
+			Self::deposit_event(RawEvent::SettlerFeePaid(settler.clone(), bet.currency_id, bet.settler_fee));
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,65 @@
+//! A bounded ledger of finished bets.
+//!
+//! Every settled or cancelled bet is appended to its better's history, which keeps the last
+//! `MaxBetHistory` records. Records older than `BetHistoryRetention` blocks can be pruned by
+//! anyone with `prune_history`. A bet's fairness record is dropped with its history record.
+//! Per-currency totals are kept for the lifetime of the chain.
+
+use crate::*;
+use sp_runtime::traits::Saturating;
+
+impl<T: Trait> Module<T> {
+	/// Append `bet` to its better's history and add it to the totals of its currency.
+	pub(crate) fn record_bet(bet: &PendingBetOf<T>, outcome: BetOutcome, payout: BalanceOf<T>) {
+		let now = <system::Module<T>>::block_number();
+		BetHistory::<T>::mutate(&bet.better, |history| {
+			history.push(BetRecord {
+				id: bet.id,
+				currency_id: bet.currency_id,
+				wager: bet.wager,
+				odds: EVEN_MONEY,
+				outcome,
+				payout,
+				placed_at: bet.placed_at,
+				finished_at: now,
+			});
+			let max = T::MaxBetHistory::get() as usize;
+			if history.len() > max {
+				let excess = history.len() - max;
+				for dropped in history.drain(..excess) {
+					FairnessRecords::<T>::remove(dropped.id);
+				}
+			}
+		});
+		Totals::<T>::mutate(bet.currency_id, |totals| match outcome {
+			BetOutcome::Cancelled => totals.refunded = totals.refunded.saturating_add(payout),
+			BetOutcome::Won | BetOutcome::Lost => {
+				totals.settled = totals.settled.saturating_add(1);
+				totals.wagered = totals.wagered.saturating_add(bet.wager);
+				totals.paid_out = totals.paid_out.saturating_add(payout);
+			}
+		});
+	}
+
+	/// Drop the records of `who` older than `BetHistoryRetention` and return how many were dropped.
+	pub(crate) fn prune_expired_history(who: &T::AccountId) -> u32 {
+		let now = <system::Module<T>>::block_number();
+		let retention = T::BetHistoryRetention::get();
+		let mut history = Self::bet_history(who);
+		let before = history.len();
+		history.retain(|record| {
+			let keep = record.finished_at.saturating_add(retention) > now;
+			if !keep {
+				FairnessRecords::<T>::remove(record.id);
+			}
+			keep
+		});
+		let pruned = (before - history.len()) as u32;
+		if history.is_empty() {
+			BetHistory::<T>::remove(who);
+		} else if pruned > 0 {
+			BetHistory::<T>::insert(who, history);
+		}
+		pruned
+	}
+}
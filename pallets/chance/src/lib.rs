@@ -2,20 +2,42 @@
 
 
 use frame_support::{
-	traits::{Currency, Vec, ExistenceRequirement::{KeepAlive, AllowDeath}, Get},
-	decl_module, decl_storage, decl_event, decl_error, dispatch, ensure, debug
+	traits::{Vec, Get},
+	decl_module, decl_storage, decl_event, decl_error, dispatch, ensure, debug, transactional, weights::Weight
 };
 
 use frame_system::{self as system, ensure_signed};
 use pallet_pooler as pooler;
 use pallet_admin as admin;
+use pallet_chance_fairness as fairness;
+use orml_traits::{MultiCurrency, MultiReservableCurrency};
 use sp_runtime::{
-    traits::{AccountIdConversion},
-    ModuleId
+    traits::{AccountIdConversion, Zero},
+    ModuleId, Perbill, RuntimeDebug
 };
-use core::ops::{Mul, Div};
+use codec::{Encode, Decode};
+use sp_core::sr25519;
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+use core::ops::Mul;
 use core::convert::TryInto;
 
+mod fees;
+mod circuit_breaker;
+mod history;
+mod responsible_gaming;
+mod strategies;
+mod referrals;
+mod provably_fair;
+pub mod migrations;
+
+pub use responsible_gaming::{GamingLimit, GamingStatus, GamingUsage, LimitPeriod, PeriodStatus};
+pub use provably_fair::{BetVerification, FairnessRecord, Reveal, Subject, Verdict};
+pub use referrals::{ReferralReward, ReferralStats};
+pub use strategies::{StakeProgression, StopReason, Strategy, StrategyId, StrategyOf};
+pub use pooler::PoolStatus;
+use migrations::Releases;
+
 #[cfg(test)]
 mod mock;
 
@@ -25,26 +47,255 @@ mod tests;
 pub trait Trait: frame_system::Trait + pooler::Trait + admin::Trait {
 	/// Because this pallet emits events, it depends on the runtime's definition of an event.
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
-	type Currency: Currency<Self::AccountId>;
+	/// The currencies that can be wagered. A bet is placed against the pool of the same currency.
+	/// Betting strategies reserve their funds up front.
+	type Currency: MultiReservableCurrency<Self::AccountId>;
+	/// The decimals bet fees are scaled by, until changed through pallet_admin.
 	type SystemDecimals: Get<u128>;
+	/// The account that receives the treasury's share of bet fees.
+	type TreasuryAccount: Get<Self::AccountId>;
+	/// The share of each bet fee paid to the treasury.
+	type TreasuryCut: Get<Perbill>;
+	/// The share of each bet fee paid to the settler that settles the bet.
+	type SettlerCut: Get<Perbill>;
+	/// The number of blocks over which pool losses are accumulated by the circuit breaker.
+	type CircuitBreakerWindow: Get<Self::BlockNumber>;
+	/// The share of a pool that may be lost within one window before betting and settlement
+	/// are paused.
+	type CircuitBreakerThreshold: Get<Perbill>;
+	/// Bets settled more than this many blocks after being placed count as missed settlements.
+	type SettlementDeadline: Get<Self::BlockNumber>;
+	/// The number of blocks after placement during which a bet can be cancelled. A bet's outcome
+	/// depends on the hash of the block its window closes in, so it can't be known while the bet
+	/// can still be cancelled.
+	type CancellationWindow: Get<Self::BlockNumber>;
+	/// The share of the wager kept by the pool when a bet is cancelled.
+	type CancellationFee: Get<Perbill>;
+	/// The number of finished bets kept in each account's history.
+	type MaxBetHistory: Get<u32>;
+	/// How long a finished bet stays in history before it may be pruned.
+	type BetHistoryRetention: Get<Self::BlockNumber>;
+	/// The number of blocks in a day, which sets the length of responsible-gaming periods.
+	type BlocksPerDay: Get<Self::BlockNumber>;
+	/// How long a player waits before a looser responsible-gaming limit takes effect.
+	type LimitIncreaseCooldown: Get<Self::BlockNumber>;
+	/// The share of each bet fee credited to the better's referrer, out of the LP share.
+	type ReferralCut: Get<Perbill>;
+	/// The longest chain of referrers walked when checking for referral cycles.
+	type MaxReferralDepth: Get<u32>;
+	/// The currency bets were placed in before bets were priced per currency.
+	type LegacyCurrencyId: Get<CurrencyIdOf<Self>>;
 
 }
 
 type AccountIdOf<T> = <T as system::Trait>::AccountId;
-pub type BalanceOf<T> = <<T as Trait>::Currency as Currency<AccountIdOf<T>>>::Balance;
+pub type BalanceOf<T> = <<T as Trait>::Currency as MultiCurrency<AccountIdOf<T>>>::Balance;
+pub type CurrencyIdOf<T> = <<T as Trait>::Currency as MultiCurrency<AccountIdOf<T>>>::CurrencyId;
+pub type BetId = u64;
+pub type PendingBetOf<T> = PendingBet<AccountIdOf<T>, CurrencyIdOf<T>, BalanceOf<T>, <T as system::Trait>::BlockNumber>;
+
+/// A bet waiting for the offchain worker to settle it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct PendingBet<AccountId, CurrencyId, Balance, BlockNumber> {
+	pub id: BetId,
+	pub better: AccountId,
+	pub currency_id: CurrencyId,
+	/// The stake left once the fee is taken; a win pays out twice this.
+	pub wager: Balance,
+	/// The settler's share of the bet fee, held in the pool until the bet is settled.
+	pub settler_fee: Balance,
+	pub placed_at: BlockNumber,
+}
+
+/// The payout multiple of a winning bet.
+pub const EVEN_MONEY: u32 = 2;
+
+pub type BetRecordOf<T> = BetRecord<CurrencyIdOf<T>, BalanceOf<T>, <T as system::Trait>::BlockNumber>;
+
+/// How a bet ended.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum BetOutcome {
+	Won,
+	Lost,
+	Cancelled,
+}
+
+/// A finished bet in an account's history.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BetRecord<CurrencyId, Balance, BlockNumber> {
+	pub id: BetId,
+	pub currency_id: CurrencyId,
+	pub wager: Balance,
+	/// The payout multiple on a win.
+	pub odds: u32,
+	pub outcome: BetOutcome,
+	/// What the better received: the winnings, nothing, or the cancellation refund.
+	pub payout: Balance,
+	pub placed_at: BlockNumber,
+	pub finished_at: BlockNumber,
+}
+
+/// Running totals of the bets placed in one currency.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BetTotals<Balance> {
+	/// The number of settled bets.
+	pub settled: u64,
+	/// The wagers of all settled bets.
+	pub wagered: Balance,
+	/// The winnings paid to betters.
+	pub paid_out: Balance,
+	/// The stakes refunded by cancellations.
+	pub refunded: Balance,
+}
+
+impl<Balance: sp_runtime::traits::Saturating + Copy> BetTotals<Balance> {
+	/// What the pool won from settled bets, not counting fees.
+	pub fn house_profit(&self) -> Balance {
+		self.wagered.saturating_sub(self.paid_out)
+	}
+
+	/// What the pool lost to settled bets, not counting fees.
+	pub fn house_loss(&self) -> Balance {
+		self.paid_out.saturating_sub(self.wagered)
+	}
+}
+
+/// The bets waiting for a settler.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct SettlementQueue<BlockNumber> {
+	/// The number of pending bets.
+	pub pending: u32,
+	/// The block the oldest pending bet was placed in.
+	pub oldest_placed_at: Option<BlockNumber>,
+}
+
+/// Net losses of a pool since the start of the current circuit breaker window.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct LossWindow<BlockNumber, Balance> {
+	pub start: BlockNumber,
+	/// The pool's free balance when the window opened.
+	pub opening_reserve: Balance,
+	pub losses: Balance,
+}
 
 decl_storage! {
 	trait Store for Module<T: Trait> as Chance {
-		ScheduledBet get(fn scheduled_bet): Vec<(T::AccountId, BalanceOf<T>)>;
+		ScheduledBet get(fn scheduled_bet): Vec<PendingBetOf<T>>;
+		/// The id of the next bet placed.
+		NextBetId get(fn next_bet_id): BetId;
+		/// The circuit breaker's loss accounting for each pool currency.
+		LossWindows get(fn loss_window): map hasher(twox_64_concat) CurrencyIdOf<T> => LossWindow<T::BlockNumber, BalanceOf<T>>;
+		/// Replaces `TreasuryAccount` as the receiver of treasury fees when set.
+		TreasuryOverride get(fn treasury_override): Option<T::AccountId>;
+		/// The last `MaxBetHistory` finished bets of each account, oldest first.
+		BetHistory get(fn bet_history): map hasher(blake2_128_concat) T::AccountId => Vec<BetRecordOf<T>>;
+		/// Running totals for each currency.
+		Totals get(fn bet_totals): map hasher(twox_64_concat) CurrencyIdOf<T> => BetTotals<BalanceOf<T>>;
+		/// Self-imposed limits of each player per currency and period.
+		GamingLimits get(fn gaming_limit): double_map hasher(blake2_128_concat) T::AccountId,
+			hasher(twox_64_concat) (CurrencyIdOf<T>, LimitPeriod) => GamingLimit<BalanceOf<T>>;
+		/// What each player staked and got back in the current period.
+		GamingUsages get(fn gaming_usage): double_map hasher(blake2_128_concat) T::AccountId,
+			hasher(twox_64_concat) (CurrencyIdOf<T>, LimitPeriod) => GamingUsage<BalanceOf<T>, T::BlockNumber>;
+		/// Looser limits waiting out the cool-down, with the block they take effect.
+		PendingLimitIncreases get(fn pending_limit_increase): double_map hasher(blake2_128_concat) T::AccountId,
+			hasher(twox_64_concat) (CurrencyIdOf<T>, LimitPeriod) => Option<(GamingLimit<BalanceOf<T>>, T::BlockNumber)>;
+		/// Players who excluded themselves from betting, with the block the exclusion ends.
+		SelfExclusions get(fn self_excluded_until): map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+		/// The id of the next strategy started.
+		NextStrategyId get(fn next_strategy_id): StrategyId;
+		/// Running betting strategies.
+		Strategies get(fn strategy): map hasher(twox_64_concat) StrategyId => Option<StrategyOf<T>>;
+		/// The strategy that placed each pending strategy bet.
+		StrategyBets get(fn strategy_of_bet): map hasher(twox_64_concat) BetId => Option<StrategyId>;
+		/// The referrer each player named.
+		Referrers get(fn referrer_of): map hasher(blake2_128_concat) T::AccountId => Option<T::AccountId>;
+		/// The number of players each account referred.
+		ReferralCounts get(fn referral_count): map hasher(blake2_128_concat) T::AccountId => u32;
+		/// The rewards of each referrer per currency.
+		ReferralRewards get(fn referral_reward): double_map hasher(blake2_128_concat) T::AccountId,
+			hasher(twox_64_concat) CurrencyIdOf<T> => ReferralReward<BalanceOf<T>>;
+		/// The randomness each provably-fair bet was settled with, until its history is pruned.
+		FairnessRecords get(fn fairness_record): map hasher(twox_64_concat) BetId => Option<FairnessRecord<T::Hash>>;
+		/// The key the randomness oracle signs its seeds with. Nothing can be settled without it.
+		OracleKey get(fn oracle_key) config(): Option<sr25519::Public>;
+		/// The oracle seed index assigned to the next subject.
+		NextSeedIndex get(fn next_seed_index): u64;
+		/// The oracle seed index assigned to each bet or round waiting for its outcome.
+		SeedIndices get(fn seed_index): map hasher(twox_64_concat) Subject => Option<u64>;
+		/// The storage layout in use. New chains start at the latest one.
+		StorageVersion build(|_| Releases::V2): Releases;
+	}
+	add_extra_genesis {
+		/// Fee and bet limit parameters applied at genesis instead of waiting out a change delay.
+		config(parameters): Vec<admin::ParameterChange>;
+		/// Replaces `TreasuryAccount` as the receiver of treasury fees.
+		config(treasury): Option<T::AccountId>;
+		/// Operations paused from the first block.
+		config(paused): Vec<admin::Operation>;
+		build(|config: &GenesisConfig<T>| {
+			for change in &config.parameters {
+				<admin::Module<T>>::apply_parameter_change(change);
+			}
+			if let Some(treasury) = &config.treasury {
+				TreasuryOverride::<T>::put(treasury);
+			}
+			for operation in &config.paused {
+				<admin::Module<T>>::trip(*operation);
+			}
+		});
 	}
 }
 
 
 decl_event!(
-	pub enum Event<T> where AccountId = <T as frame_system::Trait>::AccountId {
-		/// Event documentation should end with an array that provides descriptive names for event
-		/// parameters. [something, who]
-		SomethingStored(u32, AccountId),
+	pub enum Event<T> where
+		AccountId = <T as frame_system::Trait>::AccountId,
+		CurrencyId = CurrencyIdOf<T>,
+		Balance = BalanceOf<T>,
+		BlockNumber = <T as frame_system::Trait>::BlockNumber,
+		Limit = GamingLimit<BalanceOf<T>>,
+	{
+		/// The treasury's share of a bet fee was paid. \[treasury, currency_id, amount\]
+		TreasuryFeePaid(AccountId, CurrencyId, Balance),
+		/// A settler was paid its share of a bet fee. \[settler, currency_id, amount\]
+		SettlerFeePaid(AccountId, CurrencyId, Balance),
+		/// The LP share of a bet fee was left in the pool. \[currency_id, amount\]
+		LiquidityFeeRetained(CurrencyId, Balance),
+		/// A pool lost too much within one window, so betting and settlement were paused.
+		/// \[currency_id, losses\]
+		CircuitBreakerTripped(CurrencyId, Balance),
+		/// Treasury fees are now paid to another account. \[treasury\]
+		TreasuryAccountChanged(AccountId),
+		/// A pending bet was cancelled and its stake refunded. \[bet_id, better, currency_id, refund\]
+		BetCancelled(BetId, AccountId, CurrencyId, Balance),
+		/// Expired records were dropped from an account's history. \[who, count\]
+		HistoryPruned(AccountId, u32),
+		/// A player tightened a limit. \[who, currency_id, period, limit\]
+		GamingLimitSet(AccountId, CurrencyId, LimitPeriod, Limit),
+		/// A player loosened a limit, effective after the cool-down.
+		/// \[who, currency_id, period, limit, effective_block\]
+		GamingLimitIncreaseScheduled(AccountId, CurrencyId, LimitPeriod, Limit, BlockNumber),
+		/// A player excluded themselves from betting. \[who, until\]
+		SelfExcluded(AccountId, BlockNumber),
+		/// A player started a betting strategy. \[strategy_id, owner\]
+		StrategyStarted(StrategyId, AccountId),
+		/// A strategy placed a bet. \[strategy_id, bet_id, stake\]
+		StrategyBetPlaced(StrategyId, BetId, Balance),
+		/// A strategy stopped and released its funds. \[strategy_id, owner, reason\]
+		StrategyStopped(StrategyId, AccountId, StopReason),
+		/// A player named their referrer. \[who, referrer\]
+		ReferrerSet(AccountId, AccountId),
+		/// A referrer was credited part of a bet fee. \[referrer, currency_id, amount\]
+		ReferralRewardAccrued(AccountId, CurrencyId, Balance),
+		/// A referrer claimed their rewards. \[referrer, currency_id, amount\]
+		ReferralRewardClaimed(AccountId, CurrencyId, Balance),
+		/// Oracle seeds are now checked against another key. \[oracle\]
+		OracleKeyChanged(sr25519::Public),
 	}
 );
 
@@ -54,8 +305,57 @@ decl_error! {
 		/// Error if module is not initiated.
 	   NotEnoughLiquidity,
 	   Other,
-	   Conversion
-
+	   Conversion,
+	   /// The bet is smaller than the minimum bet.
+	   BetBelowMinimum,
+	   /// The bet is larger than the maximum bet.
+	   BetAboveMaximum,
+	   /// The bet stakes a larger share of the pool than allowed.
+	   BetExceedsPoolRatio,
+	   /// No pending bet has this id.
+	   UnknownBet,
+	   /// Only the account that placed a bet can cancel it.
+	   NotBetOwner,
+	   /// The bet can no longer be cancelled because its randomness may have been requested.
+	   CancellationWindowClosed,
+	   /// The player has excluded themselves from betting.
+	   SelfExcluded,
+	   /// The bet would exceed the player's wager limit.
+	   WagerLimitExceeded,
+	   /// The bet could exceed the player's loss limit.
+	   LossLimitExceeded,
+	   /// Bets pay even money only.
+	   UnsupportedOdds,
+	   /// A strategy needs a non-zero stake and rounds, and a stop-loss covering the first stake.
+	   InvalidStrategy,
+	   /// The player can't reserve the strategy's stop-loss.
+	   InsufficientStrategyFunds,
+	   /// No running strategy has this id.
+	   UnknownStrategy,
+	   /// Only the owner of a strategy can stop it.
+	   NotStrategyOwner,
+	   /// A player can name their referrer only once.
+	   AlreadyReferred,
+	   /// A player can't refer themselves.
+	   SelfReferral,
+	   /// The referrer was referred, directly or not, by the player.
+	   ReferralCycle,
+	   /// The referrer's chain of referrers is longer than `MaxReferralDepth`.
+	   ReferralChainTooDeep,
+	   /// There are no unclaimed referral rewards.
+	   NothingToClaim,
+	   /// No oracle key is set, so no seed can be checked.
+	   NoOracleKey,
+	   /// The seed isn't the one at the index assigned to the bet or round.
+	   WrongSeedIndex,
+	   /// The oracle didn't sign the seed for this bet or round.
+	   InvalidOracleSignature,
+	   /// Settlement is paused.
+	   SettlementPaused,
+	   /// The block the bet's outcome depends on hasn't been built yet.
+	   OutcomeNotReady,
+	   /// The bet fee overflowed.
+	   FeeOverflow,
 	}
 }
 
@@ -68,57 +368,281 @@ decl_module! {
 		// Events must be initialized if they are used by the pallet.
 		fn deposit_event() = default;
 
+		fn on_runtime_upgrade() -> Weight {
+			#[cfg(feature = "try-runtime")]
+			let snapshot = migrations::pre_migrate::<T>();
+			let weight = migrations::migrate::<T>();
+			#[cfg(feature = "try-runtime")]
+			{
+				if let Ok(snapshot) = snapshot {
+					migrations::post_migrate::<T>(snapshot).expect("chance migration checks failed");
+				}
+			}
+			weight
+		}
+
+		/// Pay treasury fees to `treasury`. Needs root or a `TreasuryManager`.
+		#[weight = 0]
+		pub fn set_treasury_account(origin, treasury: T::AccountId) {
+			<admin::Module<T>>::ensure_root_or_role(origin, admin::Role::TreasuryManager)?;
+			TreasuryOverride::<T>::put(&treasury);
+			Self::deposit_event(RawEvent::TreasuryAccountChanged(treasury));
+		}
+
+		/// Check oracle seeds against `oracle`. Needs root or an `OracleManager`.
+		#[weight = 0]
+		pub fn set_oracle_key(origin, oracle: sr25519::Public) {
+			<admin::Module<T>>::ensure_root_or_role(origin, admin::Role::OracleManager)?;
+			OracleKey::put(oracle);
+			Self::deposit_event(RawEvent::OracleKeyChanged(oracle));
+		}
+
 		#[weight = 0]
-		pub fn bet(origin, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+		pub fn bet(origin, currency_id: CurrencyIdOf<T>, amount: BalanceOf<T>) -> dispatch::DispatchResult {
 			let who = ensure_signed(origin)?;
-			Self::ensure_liquidity(&amount)?;
-			let total_locked = <T as Trait>::Currency::free_balance(&Self::account_id());
-			// fee is proportional to size of bet
-			let system_decimals: u128 = T::SystemDecimals::get();
-			let converted_amount = TryInto::<u128>::try_into(amount).unwrap_or(u128::max_value());
-			let converted_total_locked = TryInto::<u128>::try_into(total_locked).unwrap_or(u128::max_value());
-			let fee_multiplier = 10;
-			let fee = converted_amount.mul(system_decimals).mul(fee_multiplier).div(converted_total_locked);
-			
-			let bet = converted_amount.saturating_sub(fee);
-
-			<T as Trait>::Currency::transfer(&who, &Self::account_id(), amount, KeepAlive)?;
-
-			//prep bet for offchain worker
-			ScheduledBet::<T>::try_mutate(|sch| -> dispatch::DispatchResult {
-				sch.push((who, Self::u128_to_balance(bet)));
-				Ok(())
-			})?;
+			Self::place_bet(&who, currency_id, amount)?;
+			Ok(())
+		}
+
+		/// Reserve `stop_loss` and bet `base_stake` at `odds` for up to `rounds` rounds, placing
+		/// each follow-up bet when the previous one is settled. See the strategies module.
+		#[weight = 0]
+		#[transactional]
+		pub fn start_strategy(
+			origin,
+			currency_id: CurrencyIdOf<T>,
+			base_stake: BalanceOf<T>,
+			odds: u32,
+			progression: StakeProgression,
+			rounds: u32,
+			stop_loss: BalanceOf<T>,
+			take_profit: BalanceOf<T>,
+		) {
+			let who = ensure_signed(origin)?;
+			ensure!(odds == EVEN_MONEY, Error::<T>::UnsupportedOdds);
+			ensure!(!base_stake.is_zero() && rounds > 0 && stop_loss >= base_stake, Error::<T>::InvalidStrategy);
+			ensure!(<T as Trait>::Currency::can_reserve(currency_id, &who, stop_loss), Error::<T>::InsufficientStrategyFunds);
+
+			// the first bet is placed straight away, the rest of the stop-loss is reserved
+			let bet_id = Self::place_bet(&who, currency_id, base_stake)?;
+			let reserved = stop_loss - base_stake;
+			<T as Trait>::Currency::reserve(currency_id, &who, reserved)?;
+
+			let id = Self::next_strategy_id();
+			NextStrategyId::put(id.wrapping_add(1));
+			StrategyBets::insert(bet_id, id);
+			Strategies::<T>::insert(id, Strategy {
+				owner: who.clone(),
+				currency_id,
+				base_stake,
+				progression,
+				rounds_left: rounds - 1,
+				stop_loss,
+				take_profit,
+				next_stake: base_stake,
+				staked: base_stake,
+				returned: Zero::zero(),
+				reserved,
+			});
+			Self::deposit_event(RawEvent::StrategyStarted(id, who));
+			Self::deposit_event(RawEvent::StrategyBetPlaced(id, bet_id, base_stake));
+		}
+
+		/// Stop a strategy and release its funds. A bet it already placed still gets settled.
+		#[weight = 0]
+		pub fn stop_strategy(origin, id: StrategyId) {
+			let who = ensure_signed(origin)?;
+			let strategy = Self::strategy(id).ok_or(Error::<T>::UnknownStrategy)?;
+			ensure!(strategy.owner == who, Error::<T>::NotStrategyOwner);
+			Self::stop_strategy_with(id, strategy, StopReason::Cancelled);
+		}
 
+		/// Cancel a pending bet within `CancellationWindow` blocks of placing it. The wager is
+		/// refunded minus `CancellationFee`; the bet fee is not refunded.
+		#[weight = 0]
+		#[transactional]
+		pub fn cancel_bet(origin, bet_id: BetId) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pending = ScheduledBet::<T>::try_mutate(|sch| -> Result<PendingBetOf<T>, dispatch::DispatchError> {
+				let pos = sch.iter().position(|p| p.id == bet_id).ok_or(Error::<T>::UnknownBet)?;
+				ensure!(sch[pos].better == who, Error::<T>::NotBetOwner);
+				ensure!(Self::is_cancellable(&sch[pos]), Error::<T>::CancellationWindowClosed);
+				Ok(sch.remove(pos))
+			})?;
+			let refund = pending.wager - T::CancellationFee::get() * pending.wager;
+			<T as Trait>::Currency::transfer(pending.currency_id, &Self::account_id(), &who, refund)?;
+			SeedIndices::remove(Subject::Bet(bet_id));
+			Self::record_bet(&pending, BetOutcome::Cancelled, refund);
+			Self::note_gaming_return(&who, pending.currency_id, refund);
+			Self::note_strategy_bet_cancelled(bet_id);
+			Self::deposit_event(RawEvent::BetCancelled(bet_id, who, pending.currency_id, refund));
 			Ok(())
 		}
 
-		
+		/// Limit what the caller may stake or lose in `currency_id` per `period`. A stricter limit
+		/// applies at once, a looser one after `LimitIncreaseCooldown` blocks.
+		#[weight = 0]
+		pub fn set_gaming_limit(origin, currency_id: CurrencyIdOf<T>, period: LimitPeriod, limit: GamingLimit<BalanceOf<T>>) {
+			let who = ensure_signed(origin)?;
+			let key = (currency_id, period);
+			let current = Self::current_limit(&who, currency_id, period);
+			if limit.is_within(&current) {
+				PendingLimitIncreases::<T>::remove(&who, key);
+				GamingLimits::<T>::insert(&who, key, &limit);
+				Self::deposit_event(RawEvent::GamingLimitSet(who, currency_id, period, limit));
+			} else {
+				let at = <system::Module<T>>::block_number() + T::LimitIncreaseCooldown::get();
+				PendingLimitIncreases::<T>::insert(&who, key, (limit.clone(), at));
+				Self::deposit_event(RawEvent::GamingLimitIncreaseScheduled(who, currency_id, period, limit, at));
+			}
+		}
+
+		/// Reject the caller's bets for the next `blocks` blocks. An exclusion can be extended but
+		/// not shortened.
+		#[weight = 0]
+		pub fn self_exclude(origin, blocks: T::BlockNumber) {
+			let who = ensure_signed(origin)?;
+			let until = <system::Module<T>>::block_number() + blocks;
+			let until = Self::self_excluded_until(&who).map_or(until, |current| current.max(until));
+			SelfExclusions::<T>::insert(&who, until);
+			Self::deposit_event(RawEvent::SelfExcluded(who, until));
+		}
+
+		/// Name the caller's referrer, who is then credited part of the fee of each of their bets.
+		/// Can only be done once.
+		#[weight = 0]
+		pub fn set_referrer(origin, referrer: T::AccountId) {
+			let who = ensure_signed(origin)?;
+			ensure!(Self::referrer_of(&who).is_none(), Error::<T>::AlreadyReferred);
+			Self::ensure_no_referral_cycle(&who, &referrer)?;
+			Referrers::<T>::insert(&who, &referrer);
+			ReferralCounts::<T>::mutate(&referrer, |count| *count = count.saturating_add(1));
+			Self::deposit_event(RawEvent::ReferrerSet(who, referrer));
+		}
+
+		/// Pay out the caller's unclaimed referral rewards in `currency_id`.
+		#[weight = 0]
+		pub fn claim_referral_rewards(origin, currency_id: CurrencyIdOf<T>) {
+			let who = ensure_signed(origin)?;
+			let mut reward = Self::referral_reward(&who, currency_id);
+			let amount = reward.unclaimed;
+			ensure!(!amount.is_zero(), Error::<T>::NothingToClaim);
+			<T as Trait>::Currency::transfer(currency_id, &Self::referral_account(), &who, amount)?;
+			reward.unclaimed = Zero::zero();
+			ReferralRewards::<T>::insert(&who, currency_id, reward);
+			Self::deposit_event(RawEvent::ReferralRewardClaimed(who, currency_id, amount));
+		}
+
+		/// Drop the records of `who` older than `BetHistoryRetention`. Anyone may prune.
+		#[weight = 0]
+		pub fn prune_history(origin, who: T::AccountId) {
+			ensure_signed(origin)?;
+			let pruned = Self::prune_expired_history(&who);
+			if pruned > 0 {
+				Self::deposit_event(RawEvent::HistoryPruned(who, pruned));
+			}
+		}
 	}
 }
 
 impl<T: Trait> Module<T> {
 
-	pub fn scheduled_bet_callback(origin: T::Origin, better: T::AccountId, bet: BalanceOf<T>, did_win: bool) -> dispatch::DispatchResult {
-		<admin::Module<T>>::ensure_settler(origin.clone())?;
-		debug::info!("Entering callback. {}, {:#?}", did_win, bet);
-		if did_win {
-			let winnings = bet.mul(2.into());
-			<T as Trait>::Currency::transfer(&Self::account_id(), &better, winnings.into(), AllowDeath)?;
-			// <pooler::Module<T>>::track_reserves_decrease(winnings);			
-		}
-		ScheduledBet::<T>::try_mutate(|sch| ->  dispatch::DispatchResult {
-			match sch.binary_search(&(better, bet)) {
-				Ok(pos) => {
+	/// Settle the pending bet `bet_id` with an outcome the caller derived and return it. This
+	/// isn't dispatchable: settlers go through `settle_fair_bet`.
+	#[transactional]
+	pub fn scheduled_bet_callback(origin: T::Origin, bet_id: BetId, did_win: bool) -> Result<PendingBetOf<T>, dispatch::DispatchError> {
+		let settler = <admin::Module<T>>::ensure_settler(origin.clone())?;
+		ensure!(!<admin::Module<T>>::is_paused(admin::Operation::Settle), Error::<T>::SettlementPaused);
+		debug::info!("Entering callback. {}, {:#?}", did_win, bet_id);
+		let pending = ScheduledBet::<T>::try_mutate(|sch| -> Result<PendingBetOf<T>, dispatch::DispatchError> {
+			match sch.iter().position(|p| p.id == bet_id) {
+				Some(pos) => {
 					debug::info!("Found pending tx; removing.");
-					sch.remove(pos);
+					Ok(sch.remove(pos))
 				},
-				Err(_) => Err(Error::<T>::Other)?,
-			};
+				None => Err(Error::<T>::UnknownBet.into()),
+			}
+		})?;
+		SeedIndices::remove(Subject::Bet(bet_id));
+		let (better, currency_id, bet) = (pending.better.clone(), pending.currency_id, pending.wager);
+		let payout = if did_win {
+			let winnings = bet.mul(EVEN_MONEY.into());
+			<T as Trait>::Currency::transfer(currency_id, &Self::account_id(), &better, winnings)?;
+			winnings
+		} else {
+			Zero::zero()
+		};
+		Self::pay_settler_fee(&settler, &pending)?;
+		Self::note_settlement(currency_id, bet, did_win);
+		Self::record_bet(&pending, if did_win { BetOutcome::Won } else { BetOutcome::Lost }, payout);
+		Self::note_gaming_return(&better, currency_id, payout);
+		Self::note_strategy_bet_settled(&pending, did_win, payout);
+		let latency = <system::Module<T>>::block_number() - pending.placed_at;
+		<admin::Module<T>>::note_settlement(&settler, latency);
+		if latency > T::SettlementDeadline::get() {
+			<admin::Module<T>>::note_missed_settlement(&settler);
+		}
+		Ok(pending)
+
+	}
+
+	/// Take `amount` from `who` and queue an even-money bet for the offchain worker.
+	#[transactional]
+	pub(crate) fn place_bet(who: &T::AccountId, currency_id: CurrencyIdOf<T>, amount: BalanceOf<T>) -> Result<BetId, dispatch::DispatchError> {
+		ensure!(!amount.is_zero(), Error::<T>::BetBelowMinimum);
+		Self::ensure_liquidity(currency_id, &amount)?;
+		let total_locked = <T as Trait>::Currency::free_balance(currency_id, &Self::account_id());
+		ensure!(!total_locked.is_zero(), Error::<T>::NotEnoughLiquidity);
+		// fee is proportional to size of bet
+		let system_decimals: u128 = <admin::Module<T>>::system_decimals().unwrap_or_else(T::SystemDecimals::get);
+		let converted_amount = TryInto::<u128>::try_into(amount).unwrap_or(u128::max_value());
+		let converted_total_locked = TryInto::<u128>::try_into(total_locked).unwrap_or(u128::max_value());
+		Self::ensure_bet_limits(converted_amount, converted_total_locked)?;
+		Self::ensure_within_gaming_limits(who, currency_id, amount)?;
+		let fee_multiplier = <admin::Module<T>>::fee_multiplier();
+		let fee = converted_amount.checked_mul(system_decimals)
+			.and_then(|fee| fee.checked_mul(fee_multiplier))
+			.and_then(|fee| fee.checked_div(converted_total_locked))
+			.ok_or(Error::<T>::FeeOverflow)?;
+		// a small pool would otherwise charge more than the bet
+		let fee = fee.min(converted_amount);
+		let bet = converted_amount.saturating_sub(fee);
+
+		<T as Trait>::Currency::transfer(currency_id, who, &Self::account_id(), amount)?;
+		Self::note_gaming_stake(who, currency_id, amount);
+		let settler_fee = Self::distribute_bet_fee(who, currency_id, fee)?;
+
+		//prep bet for offchain worker
+		let id = Self::next_bet_id();
+		NextBetId::put(id.wrapping_add(1));
+		Self::assign_seed_index(Subject::Bet(id));
+		ScheduledBet::<T>::try_mutate(|sch| -> dispatch::DispatchResult {
+			sch.push(PendingBet {
+				id,
+				better: who.clone(),
+				currency_id,
+				wager: Self::u128_to_balance(bet),
+				settler_fee,
+				placed_at: <system::Module<T>>::block_number(),
+			});
 			Ok(())
 		})?;
-		Ok(())
 
+		Ok(id)
+	}
+
+	/// The bets waiting for a settler.
+	pub fn settlement_queue() -> SettlementQueue<T::BlockNumber> {
+		let pending = Self::scheduled_bet();
+		SettlementQueue {
+			pending: pending.len() as u32,
+			oldest_placed_at: pending.iter().map(|bet| bet.placed_at).min(),
+		}
+	}
+
+	/// Whether `bet` can still be cancelled. The offchain worker skips such bets.
+	pub fn is_cancellable(bet: &PendingBetOf<T>) -> bool {
+		<system::Module<T>>::block_number() < bet.placed_at + T::CancellationWindow::get()
 	}
 
 	pub fn account_id() -> T::AccountId{
@@ -126,12 +650,19 @@ impl<T: Trait> Module<T> {
         PALLET_ID.into_account()
 	}
 	
-	fn ensure_liquidity(amount: &BalanceOf<T>) -> dispatch::DispatchResult {
-		let current_balance = <T as Trait>::Currency::free_balance(&Self::account_id());
+	fn ensure_liquidity(currency_id: CurrencyIdOf<T>, amount: &BalanceOf<T>) -> dispatch::DispatchResult {
+		let current_balance = <T as Trait>::Currency::free_balance(currency_id, &Self::account_id());
 		ensure!(amount <= &current_balance, Error::<T>::NotEnoughLiquidity);
 		Ok(())
 	}
 
+	fn ensure_bet_limits(amount: u128, total_locked: u128) -> dispatch::DispatchResult {
+		ensure!(amount >= <admin::Module<T>>::min_bet(), Error::<T>::BetBelowMinimum);
+		ensure!(amount <= <admin::Module<T>>::max_bet(), Error::<T>::BetAboveMaximum);
+		ensure!(amount <= <admin::Module<T>>::max_bet_pool_ratio() * total_locked, Error::<T>::BetExceedsPoolRatio);
+		Ok(())
+	}
+
 	fn u128_to_balance(input: u128) -> BalanceOf<T> {
 		input.try_into().unwrap_or(0.into())
 	}
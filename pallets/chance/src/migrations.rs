@@ -0,0 +1,105 @@
+//! Storage migrations.
+//!
+//! `V1` is the layout bets had before they were priced per currency: `ScheduledBet` held a
+//! `(better, wager)` pair for each pending bet. `V2` turns each pair into a `PendingBet` in
+//! `LegacyCurrencyId`, with a fresh id, no settler fee held back, an oracle seed index and a
+//! cancellation window that has already closed.
+//!
+//! With the `try-runtime` feature, `pre_migrate` and `post_migrate` check the state around the
+//! migration on every runtime upgrade.
+
+use crate::*;
+use frame_support::{storage::StorageValue, weights::Weight};
+use sp_runtime::traits::Saturating;
+
+/// The storage layouts of this pallet.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+	/// Pending bets as `(better, wager)` pairs.
+	V1,
+	/// Pending bets as `PendingBet` with oracle seed indices.
+	V2,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1
+	}
+}
+
+type LegacyBets<T> = Vec<(<T as system::Trait>::AccountId, BalanceOf<T>)>;
+
+/// What `post_migrate` checks the migrated state against.
+pub struct Snapshot<T: Trait> {
+	/// The better and wager of every pending bet.
+	pub bets: LegacyBets<T>,
+	pub next_bet_id: BetId,
+}
+
+/// Migrate storage to `V2`.
+pub fn migrate<T: Trait>() -> Weight {
+	if StorageVersion::get() != Releases::V1 {
+		return 0;
+	}
+	let currency_id = T::LegacyCurrencyId::get();
+	// the migrated bets can no longer be cancelled, so they can be settled straight away
+	let placed_at = <system::Module<T>>::block_number().saturating_sub(T::CancellationWindow::get());
+	let mut next_id = Module::<T>::next_bet_id();
+	let _ = ScheduledBet::<T>::translate::<LegacyBets<T>, _>(|bets| {
+		bets.map(|bets| bets.into_iter().map(|(better, wager)| {
+			let id = next_id;
+			next_id = next_id.wrapping_add(1);
+			PendingBet { id, better, currency_id, wager, settler_fee: Zero::zero(), placed_at }
+		}).collect())
+	});
+	let pending = Module::<T>::scheduled_bet();
+	for bet in &pending {
+		Module::<T>::assign_seed_index(Subject::Bet(bet.id));
+	}
+	NextBetId::put(next_id);
+	StorageVersion::put(Releases::V2);
+	let count = pending.len() as Weight;
+	T::DbWeight::get().reads_writes(count + 4, 2 * count + 3)
+}
+
+/// Record the pending bets before migrating.
+#[cfg(any(test, feature = "try-runtime"))]
+pub fn pre_migrate<T: Trait>() -> Result<Snapshot<T>, &'static str> {
+	if StorageVersion::get() != Releases::V1 {
+		return Err("chance storage is already migrated");
+	}
+	let bets = frame_support::storage::migration::get_storage_value::<LegacyBets<T>>(b"Chance", b"ScheduledBet", &[])
+		.unwrap_or_default();
+	Ok(Snapshot { bets, next_bet_id: Module::<T>::next_bet_id() })
+}
+
+/// Check every bet is still pending, in order, with a unique id and a seed index, and can no
+/// longer be cancelled.
+#[cfg(any(test, feature = "try-runtime"))]
+pub fn post_migrate<T: Trait>(snapshot: Snapshot<T>) -> Result<(), &'static str> {
+	if StorageVersion::get() != Releases::V2 {
+		return Err("chance storage version wasn't bumped");
+	}
+	let pending = Module::<T>::scheduled_bet();
+	if pending.len() != snapshot.bets.len() {
+		return Err("pending bets were lost");
+	}
+	for (i, (bet, (better, wager))) in pending.iter().zip(snapshot.bets.iter()).enumerate() {
+		if bet.better != *better || bet.wager != *wager || bet.currency_id != T::LegacyCurrencyId::get() {
+			return Err("a pending bet changed");
+		}
+		if bet.id != snapshot.next_bet_id.wrapping_add(i as BetId) {
+			return Err("a pending bet got the wrong id");
+		}
+		if Module::<T>::seed_index(Subject::Bet(bet.id)).is_none() {
+			return Err("a pending bet has no seed index");
+		}
+		if Module::<T>::is_cancellable(bet) {
+			return Err("a pending bet can still be cancelled");
+		}
+	}
+	if Module::<T>::next_bet_id() != snapshot.next_bet_id.wrapping_add(pending.len() as BetId) {
+		return Err("bet ids would be reused");
+	}
+	Ok(())
+}
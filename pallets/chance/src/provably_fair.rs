@@ -0,0 +1,108 @@
+//! Provably-fair settlement.
+//!
+//! Every bet is assigned the index of an oracle seed when it is placed. The offchain worker
+//! settles bets with `settle_fair_bet`, submitting the seed the oracle revealed at that index and
+//! the oracle's signature instead of an outcome. The signature is checked against `OracleKey`, the
+//! outcome is derived on chain with pallet_chance_fairness from the seed, the hash of the block
+//! the bet's cancellation window closed in and the bet id, and the inputs are kept so anyone can
+//! recompute it with `verify_bet` or offline. A bet can't be settled before that block is built,
+//! and by then it can't be cancelled.
+
+use crate::*;
+pub use pallet_chance_fairness::{Reveal, Subject, Verdict};
+use sp_core::sr25519;
+
+/// The randomness a bet was settled with.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct FairnessRecord<Hash> {
+	pub bet_id: BetId,
+	/// The seed the oracle revealed for the bet, with its index and the oracle's signature.
+	pub reveal: Reveal,
+	/// The oracle key the seed was checked against.
+	pub oracle: sr25519::Public,
+	/// The hash of the block the bet's cancellation window closed in.
+	pub block_hash: Hash,
+	/// The outcome the bet was settled with.
+	pub won: bool,
+}
+
+/// A fairness record together with the outcome recomputed from it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BetVerification<Hash> {
+	pub record: FairnessRecord<Hash>,
+	pub verdict: Verdict,
+}
+
+impl<T: Trait> Module<T> {
+	/// Settle the pending bet `bet_id` with the outcome derived from the oracle's `reveal` and
+	/// return the bet and whether it was won.
+	#[transactional]
+	pub fn settle_fair_bet(origin: T::Origin, bet_id: BetId, reveal: Reveal) -> Result<(PendingBetOf<T>, bool), dispatch::DispatchError> {
+		<admin::Module<T>>::ensure_settler(origin.clone())?;
+		ensure!(!<admin::Module<T>>::is_paused(admin::Operation::Settle), Error::<T>::SettlementPaused);
+		let placed_at = Self::scheduled_bet().iter()
+			.find(|bet| bet.id == bet_id)
+			.map(|bet| bet.placed_at)
+			.ok_or(Error::<T>::UnknownBet)?;
+		let outcome_block = placed_at + T::CancellationWindow::get();
+		ensure!(<system::Module<T>>::block_number() > outcome_block, Error::<T>::OutcomeNotReady);
+		let oracle = Self::check_reveal(Subject::Bet(bet_id), &reveal)?;
+		let block_hash = <system::Module<T>>::block_hash(outcome_block);
+		let won = match fairness::verify(&oracle, &reveal, block_hash.as_ref(), bet_id) {
+			Verdict::Won => true,
+			Verdict::Lost => false,
+			Verdict::InvalidSignature => return Err(Error::<T>::InvalidOracleSignature.into()),
+		};
+		let bet = Self::scheduled_bet_callback(origin, bet_id, won)?;
+		FairnessRecords::<T>::insert(bet_id, FairnessRecord { bet_id, reveal, oracle, block_hash, won });
+		Ok((bet, won))
+	}
+
+	/// The randomness bet `bet_id` was settled with and the outcome recomputed from it.
+	pub fn verify_bet(bet_id: BetId) -> Option<BetVerification<T::Hash>> {
+		Self::fairness_record(bet_id).map(|record| {
+			let verdict = fairness::verify(&record.oracle, &record.reveal, record.block_hash.as_ref(), record.bet_id);
+			BetVerification { record, verdict }
+		})
+	}
+
+	/// Assign `subject` the next oracle seed index. Its outcome will be derived from the seed
+	/// the oracle reveals at that index.
+	pub fn assign_seed_index(subject: Subject) -> u64 {
+		let index = Self::next_seed_index();
+		NextSeedIndex::put(index.wrapping_add(1));
+		SeedIndices::insert(subject, index);
+		index
+	}
+
+	/// Forget the seed index of `subject` once its outcome is settled.
+	pub fn release_seed_index(subject: Subject) {
+		SeedIndices::remove(subject);
+	}
+
+	/// Whether the oracle may reveal the seed at `index` for `subject`: the index is assigned to
+	/// it and, for a bet, the bet can no longer be cancelled. Rounds are only assigned an index
+	/// once they have closed.
+	pub fn seed_due(subject: Subject, index: u64) -> bool {
+		if Self::seed_index(subject) != Some(index) {
+			return false;
+		}
+		match subject {
+			Subject::Bet(bet_id) => Self::scheduled_bet().iter()
+				.find(|bet| bet.id == bet_id)
+				.map_or(false, |bet| !Self::is_cancellable(bet)),
+			Subject::Lottery(_) | Subject::Prediction(_) => true,
+		}
+	}
+
+	/// Check that `reveal` is the seed at the index assigned to `subject`, signed by the oracle,
+	/// and return the oracle's key.
+	pub fn check_reveal(subject: Subject, reveal: &Reveal) -> Result<sr25519::Public, dispatch::DispatchError> {
+		let oracle = Self::oracle_key().ok_or(Error::<T>::NoOracleKey)?;
+		ensure!(Self::seed_index(subject) == Some(reveal.index), Error::<T>::WrongSeedIndex);
+		ensure!(reveal.is_signed_by(&oracle, subject), Error::<T>::InvalidOracleSignature);
+		Ok(oracle)
+	}
+}
@@ -0,0 +1,82 @@
+//! Referral rewards.
+//!
+//! A player may name a referrer once. `ReferralCut` of the fee of every bet they place is then
+//! moved out of the LP share into a holding account and credited to the referrer, who can claim
+//! it at any time. Self-referral and referral cycles are rejected, so every chain of referrers
+//! ends; it is walked up to `MaxReferralDepth` accounts when a referrer is named.
+
+use crate::*;
+use sp_runtime::traits::Saturating;
+
+/// What a referrer earned in one currency.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ReferralReward<Balance> {
+	/// Everything credited so far.
+	pub earned: Balance,
+	/// What is left to claim.
+	pub unclaimed: Balance,
+}
+
+/// An account's referrer, referrals and rewards in one currency.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct ReferralStats<AccountId, Balance> {
+	pub referrer: Option<AccountId>,
+	/// The number of players who named this account as their referrer.
+	pub referred: u32,
+	pub reward: ReferralReward<Balance>,
+}
+
+impl<T: Trait> Module<T> {
+	/// The account holding unclaimed referral rewards.
+	pub fn referral_account() -> T::AccountId {
+		ModuleId(*b"chn/refr").into_account()
+	}
+
+	/// Ensure naming `referrer` as the referrer of `who` wouldn't close a cycle.
+	pub(crate) fn ensure_no_referral_cycle(who: &T::AccountId, referrer: &T::AccountId) -> dispatch::DispatchResult {
+		ensure!(who != referrer, Error::<T>::SelfReferral);
+		let mut current = referrer.clone();
+		for _ in 0..T::MaxReferralDepth::get() {
+			match Self::referrer_of(&current) {
+				Some(next) => {
+					ensure!(&next != who, Error::<T>::ReferralCycle);
+					current = next;
+				},
+				None => return Ok(()),
+			}
+		}
+		Err(Error::<T>::ReferralChainTooDeep.into())
+	}
+
+	/// Credit the referrer of `better`, if any, with its cut of `fee` and return the amount
+	/// credited, which the caller takes out of the LP share.
+	pub(crate) fn pay_referral_reward(better: &T::AccountId, currency_id: CurrencyIdOf<T>, fee: u128, lp_fee: u128) -> Result<u128, dispatch::DispatchError> {
+		let referrer = match Self::referrer_of(better) {
+			Some(referrer) => referrer,
+			None => return Ok(0),
+		};
+		let reward = (T::ReferralCut::get() * fee).min(lp_fee);
+		let amount = Self::u128_to_balance(reward);
+		if amount.is_zero() {
+			return Ok(0);
+		}
+		<T as Trait>::Currency::transfer(currency_id, &Self::account_id(), &Self::referral_account(), amount)?;
+		ReferralRewards::<T>::mutate(&referrer, currency_id, |r| {
+			r.earned = r.earned.saturating_add(amount);
+			r.unclaimed = r.unclaimed.saturating_add(amount);
+		});
+		Self::deposit_event(RawEvent::ReferralRewardAccrued(referrer, currency_id, amount));
+		Ok(reward)
+	}
+
+	/// The referral stats of `who` in `currency_id`.
+	pub fn referral_stats(who: &T::AccountId, currency_id: CurrencyIdOf<T>) -> ReferralStats<T::AccountId, BalanceOf<T>> {
+		ReferralStats {
+			referrer: Self::referrer_of(who),
+			referred: Self::referral_count(who),
+			reward: Self::referral_reward(who, currency_id),
+		}
+	}
+}
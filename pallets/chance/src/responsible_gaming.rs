@@ -0,0 +1,179 @@
+//! Self-imposed player protection.
+//!
+//! A player can cap how much they stake and how much they lose in each currency per day, week
+//! and month, and can exclude themselves from betting for a while. Tightening a limit takes
+//! effect at once; loosening it only after `LimitIncreaseCooldown` blocks. `bet` checks the
+//! limits assuming the new bet is lost.
+
+use crate::*;
+use sp_runtime::traits::Saturating;
+
+/// The periods limits are tracked over.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum LimitPeriod {
+	Day,
+	Week,
+	Month,
+}
+
+impl LimitPeriod {
+	pub const ALL: [LimitPeriod; 3] = [LimitPeriod::Day, LimitPeriod::Week, LimitPeriod::Month];
+
+	/// The length of the period given the number of blocks in a day.
+	pub fn blocks<BlockNumber: From<u32> + Mul<Output = BlockNumber>>(self, day: BlockNumber) -> BlockNumber {
+		match self {
+			LimitPeriod::Day => day,
+			LimitPeriod::Week => day * 7.into(),
+			LimitPeriod::Month => day * 30.into(),
+		}
+	}
+}
+
+/// A player's limits for one currency and period. `None` means unlimited.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct GamingLimit<Balance> {
+	/// The most that may be staked.
+	pub max_wager: Option<Balance>,
+	/// The most that may be lost, net of winnings and refunds.
+	pub max_loss: Option<Balance>,
+}
+
+impl<Balance: PartialOrd> GamingLimit<Balance> {
+	/// Whether `self` is at least as strict as `other` on both caps.
+	pub fn is_within(&self, other: &Self) -> bool {
+		fn within<B: PartialOrd>(new: &Option<B>, old: &Option<B>) -> bool {
+			match (new, old) {
+				(_, None) => true,
+				(Some(new), Some(old)) => new <= old,
+				(None, Some(_)) => false,
+			}
+		}
+		within(&self.max_wager, &other.max_wager) && within(&self.max_loss, &other.max_loss)
+	}
+}
+
+/// What a player staked and got back in the current period.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct GamingUsage<Balance, BlockNumber> {
+	pub start: BlockNumber,
+	pub staked: Balance,
+	/// Winnings and cancellation refunds.
+	pub returned: Balance,
+}
+
+impl<Balance: Saturating + Copy, BlockNumber> GamingUsage<Balance, BlockNumber> {
+	pub fn loss(&self) -> Balance {
+		self.staked.saturating_sub(self.returned)
+	}
+}
+
+/// The limit, usage and pending increase of one period.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct PeriodStatus<Balance, BlockNumber> {
+	pub period: LimitPeriod,
+	pub limit: GamingLimit<Balance>,
+	pub usage: GamingUsage<Balance, BlockNumber>,
+	/// A looser limit and the block it takes effect.
+	pub pending_increase: Option<(GamingLimit<Balance>, BlockNumber)>,
+}
+
+/// A player's responsible-gaming settings for one currency.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct GamingStatus<Balance, BlockNumber> {
+	pub self_excluded_until: Option<BlockNumber>,
+	pub periods: Vec<PeriodStatus<Balance, BlockNumber>>,
+}
+
+impl<T: Trait> Module<T> {
+	/// Ensure `who` isn't self-excluded and may stake `amount` more in `currency_id`. Every game
+	/// played against the pool checks this before taking a stake.
+	pub fn ensure_within_gaming_limits(who: &T::AccountId, currency_id: CurrencyIdOf<T>, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+		let now = <system::Module<T>>::block_number();
+		if let Some(until) = Self::self_excluded_until(who) {
+			ensure!(now >= until, Error::<T>::SelfExcluded);
+		}
+		for period in LimitPeriod::ALL.iter() {
+			let limit = Self::current_limit(who, currency_id, *period);
+			let usage = Self::current_usage(who, currency_id, *period);
+			if let Some(max_wager) = limit.max_wager {
+				ensure!(usage.staked.saturating_add(amount) <= max_wager, Error::<T>::WagerLimitExceeded);
+			}
+			if let Some(max_loss) = limit.max_loss {
+				ensure!(usage.loss().saturating_add(amount) <= max_loss, Error::<T>::LossLimitExceeded);
+			}
+		}
+		Ok(())
+	}
+
+	/// Add a stake of `amount` to every period of `who`.
+	pub fn note_gaming_stake(who: &T::AccountId, currency_id: CurrencyIdOf<T>, amount: BalanceOf<T>) {
+		for period in LimitPeriod::ALL.iter() {
+			let mut usage = Self::current_usage(who, currency_id, *period);
+			usage.staked = usage.staked.saturating_add(amount);
+			GamingUsages::<T>::insert(who, (currency_id, *period), usage);
+		}
+	}
+
+	/// Add winnings or a refund of `amount` to every period of `who`.
+	pub fn note_gaming_return(who: &T::AccountId, currency_id: CurrencyIdOf<T>, amount: BalanceOf<T>) {
+		if amount.is_zero() {
+			return;
+		}
+		for period in LimitPeriod::ALL.iter() {
+			let mut usage = Self::current_usage(who, currency_id, *period);
+			usage.returned = usage.returned.saturating_add(amount);
+			GamingUsages::<T>::insert(who, (currency_id, *period), usage);
+		}
+	}
+
+	/// The limit in force, applying a pending increase whose cool-down has passed.
+	pub(crate) fn current_limit(who: &T::AccountId, currency_id: CurrencyIdOf<T>, period: LimitPeriod) -> GamingLimit<BalanceOf<T>> {
+		let key = (currency_id, period);
+		match Self::pending_limit_increase(who, key) {
+			Some((limit, at)) if at <= <system::Module<T>>::block_number() => {
+				PendingLimitIncreases::<T>::remove(who, key);
+				GamingLimits::<T>::insert(who, key, &limit);
+				limit
+			}
+			_ => Self::gaming_limit(who, key),
+		}
+	}
+
+	/// The usage of the current period, starting a new period if the last one is over.
+	fn current_usage(who: &T::AccountId, currency_id: CurrencyIdOf<T>, period: LimitPeriod) -> GamingUsage<BalanceOf<T>, T::BlockNumber> {
+		let now = <system::Module<T>>::block_number();
+		let usage = Self::gaming_usage(who, (currency_id, period));
+		if now >= usage.start.saturating_add(period.blocks(T::BlocksPerDay::get())) || usage.start.is_zero() {
+			GamingUsage { start: now, staked: Zero::zero(), returned: Zero::zero() }
+		} else {
+			usage
+		}
+	}
+
+	/// The responsible-gaming settings of `who` in `currency_id`.
+	pub fn gaming_status(who: &T::AccountId, currency_id: CurrencyIdOf<T>) -> GamingStatus<BalanceOf<T>, T::BlockNumber> {
+		let now = <system::Module<T>>::block_number();
+		GamingStatus {
+			self_excluded_until: Self::self_excluded_until(who).filter(|until| *until > now),
+			periods: LimitPeriod::ALL.iter().map(|period| {
+				let key = (currency_id, *period);
+				let pending_increase = Self::pending_limit_increase(who, key);
+				let limit = match &pending_increase {
+					Some((limit, at)) if *at <= now => limit.clone(),
+					_ => Self::gaming_limit(who, key),
+				};
+				PeriodStatus {
+					period: *period,
+					limit,
+					usage: Self::current_usage(who, currency_id, *period),
+					pending_increase: pending_increase.filter(|(_, at)| *at > now),
+				}
+			}).collect(),
+		}
+	}
+}
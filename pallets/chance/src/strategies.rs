@@ -0,0 +1,149 @@
+//! Automatic betting strategies.
+//!
+//! A player reserves `stop_loss` up front and the pallet places up to `rounds` bets on their
+//! behalf, one at a time. The first bet is placed when the strategy starts; each follow-up bet is
+//! placed when the previous one is settled, staking the base stake (`Fixed`) or doubling it after
+//! every loss (`Martingale`). Winnings are reserved along with the rest of the strategy's funds.
+//! The strategy stops when its rounds run out, its profit reaches `take_profit`, the next stake
+//! could push its loss past `stop_loss`, or a bet can't be placed, and whatever is still reserved
+//! is released.
+
+use crate::*;
+use sp_runtime::traits::Saturating;
+
+pub type StrategyId = u32;
+
+/// How the stake of each follow-up bet is chosen.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum StakeProgression {
+	/// Always stake the base stake.
+	Fixed,
+	/// Double the stake after a loss and go back to the base stake after a win.
+	Martingale,
+}
+
+/// Why a strategy stopped.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum StopReason {
+	/// All rounds were played.
+	Completed,
+	TakeProfit,
+	StopLoss,
+	/// The owner stopped the strategy or cancelled one of its bets.
+	Cancelled,
+	/// A bet couldn't be placed, e.g. because of a limit or a pause.
+	Failed,
+}
+
+/// The parameters and progress of a running strategy.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Strategy<AccountId, CurrencyId, Balance> {
+	pub owner: AccountId,
+	pub currency_id: CurrencyId,
+	pub base_stake: Balance,
+	pub progression: StakeProgression,
+	pub rounds_left: u32,
+	/// The most the strategy may lose overall; also what is reserved up front.
+	pub stop_loss: Balance,
+	/// The profit at which the strategy stops. Zero never stops early.
+	pub take_profit: Balance,
+	/// The stake of the next bet.
+	pub next_stake: Balance,
+	/// The sum of all stakes placed.
+	pub staked: Balance,
+	/// The sum of all payouts received.
+	pub returned: Balance,
+	/// What is still reserved for the strategy.
+	pub reserved: Balance,
+}
+
+impl<AccountId, CurrencyId, Balance: Saturating + Copy> Strategy<AccountId, CurrencyId, Balance> {
+	pub fn profit(&self) -> Balance {
+		self.returned.saturating_sub(self.staked)
+	}
+
+	pub fn loss(&self) -> Balance {
+		self.staked.saturating_sub(self.returned)
+	}
+}
+
+pub type StrategyOf<T> = Strategy<AccountIdOf<T>, CurrencyIdOf<T>, BalanceOf<T>>;
+
+impl<T: Trait> Module<T> {
+	/// Place the next bet of strategy `id`, or stop it if a stop condition is met.
+	pub(crate) fn advance_strategy(id: StrategyId, mut strategy: StrategyOf<T>) {
+		let reason = if strategy.rounds_left == 0 {
+			Some(StopReason::Completed)
+		} else if !strategy.take_profit.is_zero() && strategy.profit() >= strategy.take_profit {
+			Some(StopReason::TakeProfit)
+		} else if strategy.loss().saturating_add(strategy.next_stake) > strategy.stop_loss
+			|| strategy.next_stake > strategy.reserved
+		{
+			Some(StopReason::StopLoss)
+		} else if <admin::Module<T>>::is_paused(admin::Operation::Bet) {
+			Some(StopReason::Failed)
+		} else {
+			None
+		};
+		if let Some(reason) = reason {
+			return Self::stop_strategy_with(id, strategy, reason);
+		}
+
+		let stake = strategy.next_stake;
+		<T as Trait>::Currency::unreserve(strategy.currency_id, &strategy.owner, stake);
+		strategy.reserved = strategy.reserved.saturating_sub(stake);
+		match Self::place_bet(&strategy.owner, strategy.currency_id, stake) {
+			Ok(bet_id) => {
+				strategy.rounds_left -= 1;
+				strategy.staked = strategy.staked.saturating_add(stake);
+				StrategyBets::insert(bet_id, id);
+				Strategies::<T>::insert(id, strategy);
+				Self::deposit_event(RawEvent::StrategyBetPlaced(id, bet_id, stake));
+			},
+			Err(e) => {
+				debug::warn!("Strategy {} could not place a bet: {:?}", id, e);
+				// the stake was released above and stays free
+				Self::stop_strategy_with(id, strategy, StopReason::Failed);
+			},
+		}
+	}
+
+	/// Carry on with the strategy that placed `bet`, if any, now that it paid out `payout`.
+	pub(crate) fn note_strategy_bet_settled(bet: &PendingBetOf<T>, did_win: bool, payout: BalanceOf<T>) {
+		let id = match StrategyBets::take(bet.id) {
+			Some(id) => id,
+			None => return,
+		};
+		let mut strategy = match Self::strategy(id) {
+			Some(strategy) => strategy,
+			None => return,
+		};
+		strategy.returned = strategy.returned.saturating_add(payout);
+		if !payout.is_zero() && <T as Trait>::Currency::reserve(strategy.currency_id, &strategy.owner, payout).is_ok() {
+			strategy.reserved = strategy.reserved.saturating_add(payout);
+		}
+		strategy.next_stake = match (strategy.progression, did_win) {
+			(StakeProgression::Martingale, false) => strategy.next_stake.saturating_mul(2u32.into()),
+			_ => strategy.base_stake,
+		};
+		Self::advance_strategy(id, strategy);
+	}
+
+	/// Stop the strategy that placed bet `bet_id`, if any, because the bet was cancelled.
+	pub(crate) fn note_strategy_bet_cancelled(bet_id: BetId) {
+		if let Some(id) = StrategyBets::take(bet_id) {
+			if let Some(strategy) = Self::strategy(id) {
+				Self::stop_strategy_with(id, strategy, StopReason::Cancelled);
+			}
+		}
+	}
+
+	/// Release the strategy's funds and remove it.
+	pub(crate) fn stop_strategy_with(id: StrategyId, strategy: StrategyOf<T>, reason: StopReason) {
+		<T as Trait>::Currency::unreserve(strategy.currency_id, &strategy.owner, strategy.reserved);
+		Strategies::<T>::remove(id);
+		Self::deposit_event(RawEvent::StrategyStopped(id, strategy.owner, reason));
+	}
+}
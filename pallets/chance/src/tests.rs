@@ -1,12 +1,26 @@
-use crate::{Error, mock::*};
-use frame_support::{assert_ok, assert_noop};
+use crate::{BetId, BetOutcome, Error, GamingLimit, LimitPeriod, PendingBet, PendingBetOf, Reveal, StakeProgression, Subject, Verdict, EVEN_MONEY, mock::*};
+use frame_support::{assert_ok, assert_noop, traits::{Currency, OnInitialize, ReservableCurrency}};
+use sp_core::{sr25519, Pair, H256};
+use sp_runtime::{DispatchError, Perbill};
+use pallet_admin::{Operation, ParameterChange, Role, MAX_FEE_MULTIPLIER, MAX_SYSTEM_DECIMALS};
+use orml_traits::{MultiCurrency, MultiReservableCurrency};
+
+fn pending(id: BetId, better: u64, currency_id: CurrencyId, wager: u128, settler_fee: u128) -> PendingBetOf<Test> {
+	PendingBet { id, better, currency_id, wager, settler_fee, placed_at: 1 }
+}
+
+/// Schedule `change` as root and run the scheduler at the block it takes effect.
+fn change_parameter(change: ParameterChange) {
+	assert_ok!(Admin::schedule_parameter_change(Origin::root(), change));
+	Scheduler::on_initialize(System::block_number() + PARAMETER_CHANGE_DELAY);
+}
 
 #[test]
 fn test_bet_small_bet() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Pooler::deposit(Origin::signed(1), 100000000000000));
-		assert_ok!(Chance::bet(Origin::signed(2), 1000000000000));
-		let bet = [(2,990000000000,),];
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		let bet = [pending(0, 2, DOT, 990000000000, 2000000000)];
 		println!("small bet{:#?}", Chance::scheduled_bet());
 		assert_eq!(Chance::scheduled_bet(), bet);
 	}
@@ -15,10 +29,826 @@ fn test_bet_small_bet() {
 #[test]
 fn test_bet_whole_pool() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Pooler::deposit(Origin::signed(1), 10000000000000));
-		assert_ok!(Chance::bet(Origin::signed(2), 10000000000000));
-		let bet = [(2,9000000000000,),];
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 10000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 10000000000000));
+		let bet = [pending(0, 2, DOT, 9000000000000, 200000000000)];
 		println!("large bet{:#?}", Chance::scheduled_bet());
 		assert_eq!(Chance::scheduled_bet(), bet);
 	}
 )}
+
+#[test]
+fn test_bet_is_placed_against_pool_of_same_currency() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), USD, 100000000000000));
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 1000000000000), Error::<Test>::NotEnoughLiquidity);
+		assert_ok!(Chance::bet(Origin::signed(2), USD, 1000000000000));
+		assert_eq!(Chance::scheduled_bet(), [pending(0, 2, USD, 990000000000, 2000000000)]);
+	}
+)}
+
+#[test]
+fn test_bet_fee_is_split_between_treasury_lps_and_settler() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		// the fee is 10000000000: 10% to the treasury straight away, 20% held for the settler
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 1000000000);
+		assert_eq!(Tokens::free_balance(DOT, &Chance::account_id()), 100999000000000);
+
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 0, false));
+		assert_eq!(Tokens::free_balance(DOT, &SETTLER), 2000000000);
+		// the remaining 70% of the fee and the lost wager stay with the LPs
+		assert_eq!(Tokens::free_balance(DOT, &Chance::account_id()), 100997000000000);
+		assert_eq!(Chance::scheduled_bet(), []);
+	}
+)}
+
+#[test]
+fn test_bet_fee_is_capped_at_the_bet_in_a_small_pool() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 1000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 100));
+		// the fee would be 10^11, far more than the bet, so all of the bet is taken as fee
+		assert_eq!(Chance::scheduled_bet(), [pending(0, 2, DOT, 0, 20)]);
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 10);
+		assert_eq!(Tokens::free_balance(DOT, &Chance::account_id()), 1090);
+	}
+)}
+
+#[test]
+fn test_settling_unknown_bet_pays_no_settler_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_noop!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 1, true), Error::<Test>::UnknownBet);
+		assert_eq!(Tokens::free_balance(DOT, &SETTLER), 0);
+	}
+)}
+
+#[test]
+fn test_bet_can_be_cancelled_within_window() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert!(Chance::is_cancellable(&Chance::scheduled_bet()[0]));
+
+		assert_noop!(Chance::cancel_bet(Origin::signed(1), 0), Error::<Test>::NotBetOwner);
+		assert_noop!(Chance::cancel_bet(Origin::signed(2), 2), Error::<Test>::UnknownBet);
+		assert_ok!(Chance::cancel_bet(Origin::signed(2), 0));
+		// the wager is refunded minus the 10% cancellation fee
+		assert_eq!(Tokens::free_balance(DOT, &2), 1000000000000000 - 2 * 1000000000000 + 891000000000);
+		assert_eq!(Chance::scheduled_bet().len(), 1);
+		assert_eq!(Chance::scheduled_bet()[0].id, 1);
+
+		System::set_block_number(1 + CANCELLATION_WINDOW);
+		assert!(!Chance::is_cancellable(&Chance::scheduled_bet()[0]));
+		assert_noop!(Chance::cancel_bet(Origin::signed(2), 1), Error::<Test>::CancellationWindowClosed);
+	}
+)}
+
+#[test]
+fn test_finished_bets_are_recorded_in_bounded_history() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		for _ in 0..4 {
+			assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		}
+		let bets = Chance::scheduled_bet();
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 0, true));
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 1, false));
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 2, false));
+		assert_ok!(Chance::cancel_bet(Origin::signed(2), 3));
+
+		// only the last `MaxBetHistory` bets are kept
+		let history = Chance::bet_history(2);
+		assert_eq!(history.iter().map(|r| r.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+		assert_eq!(history[0].outcome, BetOutcome::Lost);
+		assert_eq!(history[0].payout, 0);
+		assert_eq!(history[2].outcome, BetOutcome::Cancelled);
+		let refund = history[2].payout;
+
+		let totals = Chance::bet_totals(DOT);
+		assert_eq!(totals.settled, 3);
+		assert_eq!(totals.wagered, bets[0].wager + bets[1].wager + bets[2].wager);
+		assert_eq!(totals.paid_out, 2 * bets[0].wager);
+		assert_eq!(totals.refunded, refund);
+		assert_eq!(totals.house_profit(), bets[1].wager + bets[2].wager - bets[0].wager);
+		assert_eq!(totals.house_loss(), 0);
+	}
+)}
+
+#[test]
+fn test_expired_history_can_be_pruned() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 0, false));
+
+		System::set_block_number(100);
+		assert_ok!(Chance::prune_history(Origin::signed(1), 2));
+		assert_eq!(Chance::bet_history(2).len(), 1);
+
+		System::set_block_number(101);
+		assert_ok!(Chance::prune_history(Origin::signed(1), 2));
+		assert!(Chance::bet_history(2).is_empty());
+		assert_eq!(Chance::bet_totals(DOT).settled, 1);
+	}
+)}
+
+const STAKE: u128 = 1000000000000;
+
+#[test]
+fn test_fixed_strategy_bets_every_round() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::start_strategy(Origin::signed(2), DOT, STAKE, EVEN_MONEY, StakeProgression::Fixed, 2, 5 * STAKE, 0));
+		// the first bet is placed at once and the rest of the stop-loss is reserved
+		assert_eq!(Tokens::reserved_balance(DOT, &2), 4 * STAKE);
+		assert_eq!(Chance::scheduled_bet().len(), 1);
+		assert_eq!(Chance::strategy_of_bet(0), Some(0));
+
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 0, false));
+		assert_eq!(Chance::scheduled_bet()[0].id, 1);
+		assert_eq!(Tokens::reserved_balance(DOT, &2), 3 * STAKE);
+
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 1, false));
+		assert!(Chance::scheduled_bet().is_empty());
+		assert!(Chance::strategy(0).is_none());
+		assert_eq!(Tokens::reserved_balance(DOT, &2), 0);
+		assert_eq!(Tokens::free_balance(DOT, &2), 1000000000000000 - 2 * STAKE);
+	}
+)}
+
+#[test]
+fn test_martingale_strategy_stops_at_stop_loss() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::start_strategy(Origin::signed(2), DOT, STAKE, EVEN_MONEY, StakeProgression::Martingale, 10, 3 * STAKE + STAKE / 2, 0));
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 0, false));
+		// the stake doubles after a loss
+		assert_eq!(Chance::strategy(0).unwrap().next_stake, 2 * STAKE);
+		assert_eq!(Tokens::reserved_balance(DOT, &2), STAKE / 2);
+
+		// another doubling would risk more than the stop-loss
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 1, false));
+		assert!(Chance::strategy(0).is_none());
+		assert!(Chance::scheduled_bet().is_empty());
+		assert_eq!(Tokens::reserved_balance(DOT, &2), 0);
+	}
+)}
+
+#[test]
+fn test_strategy_stops_at_take_profit_or_by_owner() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_noop!(
+			Chance::start_strategy(Origin::signed(2), DOT, STAKE, 3, StakeProgression::Fixed, 2, 5 * STAKE, 0),
+			Error::<Test>::UnsupportedOdds
+		);
+		assert_noop!(
+			Chance::start_strategy(Origin::signed(2), DOT, STAKE, EVEN_MONEY, StakeProgression::Fixed, 2, STAKE / 2, 0),
+			Error::<Test>::InvalidStrategy
+		);
+		assert_noop!(
+			Chance::start_strategy(Origin::signed(3), DOT, 5, EVEN_MONEY, StakeProgression::Fixed, 2, 50, 0),
+			Error::<Test>::InsufficientStrategyFunds
+		);
+
+		assert_ok!(Chance::start_strategy(Origin::signed(2), DOT, STAKE, EVEN_MONEY, StakeProgression::Fixed, 5, 5 * STAKE, STAKE / 2));
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 0, true));
+		// the winnings are past the take-profit, so everything is released
+		assert!(Chance::strategy(0).is_none());
+		assert_eq!(Tokens::reserved_balance(DOT, &2), 0);
+
+		assert_ok!(Chance::start_strategy(Origin::signed(2), DOT, STAKE, EVEN_MONEY, StakeProgression::Fixed, 5, 5 * STAKE, 0));
+		assert_noop!(Chance::stop_strategy(Origin::signed(1), 1), Error::<Test>::NotStrategyOwner);
+		assert_ok!(Chance::stop_strategy(Origin::signed(2), 1));
+		assert_eq!(Tokens::reserved_balance(DOT, &2), 0);
+		// the bet already placed is still settled, without a follow-up
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 1, false));
+		assert!(Chance::scheduled_bet().is_empty());
+	}
+)}
+
+#[test]
+fn test_referrers_cannot_form_cycles() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Chance::set_referrer(Origin::signed(2), 2), Error::<Test>::SelfReferral);
+		assert_ok!(Chance::set_referrer(Origin::signed(2), 1));
+		assert_noop!(Chance::set_referrer(Origin::signed(2), 3), Error::<Test>::AlreadyReferred);
+		assert_noop!(Chance::set_referrer(Origin::signed(1), 2), Error::<Test>::ReferralCycle);
+
+		assert_ok!(Chance::set_referrer(Origin::signed(3), 2));
+		assert_ok!(Chance::set_referrer(Origin::signed(4), 3));
+		assert_noop!(Chance::set_referrer(Origin::signed(1), 4), Error::<Test>::ReferralCycle);
+		// the chain above 4 is as long as `MaxReferralDepth`
+		assert_noop!(Chance::set_referrer(Origin::signed(5), 4), Error::<Test>::ReferralChainTooDeep);
+		assert_eq!(Chance::referral_stats(&2, DOT).referrer, Some(1));
+		assert_eq!(Chance::referral_count(1), 1);
+	}
+)}
+
+#[test]
+fn test_referrer_earns_part_of_bet_fee() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::set_referrer(Origin::signed(2), 3));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		// 10% of the 10000000000 fee is taken out of the LP share
+		assert_eq!(Tokens::free_balance(DOT, &Chance::referral_account()), 1000000000);
+		assert_eq!(Tokens::free_balance(DOT, &Chance::account_id()), 100998000000000);
+		assert_eq!(Chance::referral_stats(&3, DOT).reward.unclaimed, 1000000000);
+
+		assert_ok!(Chance::claim_referral_rewards(Origin::signed(3), DOT));
+		assert_eq!(Tokens::free_balance(DOT, &3), 10 + 1000000000);
+		let stats = Chance::referral_stats(&3, DOT);
+		assert_eq!((stats.referred, stats.reward.earned, stats.reward.unclaimed), (1, 1000000000, 0));
+		assert_noop!(Chance::claim_referral_rewards(Origin::signed(3), DOT), Error::<Test>::NothingToClaim);
+	}
+)}
+
+/// The block the outcomes of bets placed in block 1 depend on.
+const OUTCOME_BLOCK: u64 = 1 + CANCELLATION_WINDOW;
+
+/// Build the block the outcomes of bets placed in block 1 depend on and move past it.
+fn close_cancellation_windows() {
+	frame_system::BlockHash::<Test>::insert(OUTCOME_BLOCK, H256::repeat_byte(3));
+	System::set_block_number(OUTCOME_BLOCK + 1);
+}
+
+/// A seed settling bet `bet_id`, placed in block 1, with the given outcome.
+fn seed_for(bet_id: BetId, won: bool) -> Vec<u8> {
+	let block_hash = System::block_hash(OUTCOME_BLOCK);
+	(0u8..).map(|i| vec![i; 32])
+		.find(|seed| pallet_chance_fairness::is_win(seed, block_hash.as_ref(), bet_id) == won)
+		.unwrap()
+}
+
+/// The test oracle's reveal of `seed` at `index` for `subject`.
+fn reveal_at(subject: Subject, index: u64, seed: Vec<u8>) -> Reveal {
+	let signature = oracle().sign(&pallet_chance_fairness::signed_message(subject, index, &seed));
+	Reveal { index, seed, signature: signature.0.to_vec() }
+}
+
+/// The test oracle's reveal of `seed` for `subject`, at the index the chain assigned it.
+fn reveal(subject: Subject, seed: Vec<u8>) -> Reveal {
+	reveal_at(subject, Chance::seed_index(subject).unwrap(), seed)
+}
+
+#[test]
+fn test_seeds_are_due_once_assigned_and_the_bet_cannot_be_cancelled() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		// the bet can still be cancelled
+		assert!(!Chance::seed_due(Subject::Bet(0), 0));
+		System::set_block_number(1 + CANCELLATION_WINDOW);
+		assert!(Chance::seed_due(Subject::Bet(0), 0));
+		// indices assigned to another subject or not assigned yet aren't due
+		assert!(!Chance::seed_due(Subject::Bet(1), 0));
+		assert!(!Chance::seed_due(Subject::Bet(0), 1));
+		assert!(!Chance::seed_due(Subject::Lottery(0), 0));
+	});
+}
+
+#[test]
+fn test_fair_settlement_records_verifiable_randomness() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_eq!(Chance::seed_index(Subject::Bet(0)), Some(0));
+		assert_eq!(Chance::seed_index(Subject::Bet(1)), Some(1));
+		// the outcome isn't settled before the block it depends on is built
+		System::set_block_number(OUTCOME_BLOCK);
+		assert_noop!(
+			Chance::settle_fair_bet(Origin::signed(SETTLER), 0, reveal(Subject::Bet(0), vec![0; 32])),
+			Error::<Test>::OutcomeNotReady
+		);
+		close_cancellation_windows();
+
+		let seed = seed_for(0, true);
+		let fair = reveal(Subject::Bet(0), seed.clone());
+		assert_noop!(
+			Chance::settle_fair_bet(Origin::signed(SETTLER), 0, reveal_at(Subject::Bet(0), 1, seed.clone())),
+			Error::<Test>::WrongSeedIndex
+		);
+		// A seed signed for another bet can't settle this one.
+		let mut replayed = reveal_at(Subject::Bet(1), 0, seed.clone());
+		assert_noop!(
+			Chance::settle_fair_bet(Origin::signed(SETTLER), 0, replayed.clone()),
+			Error::<Test>::InvalidOracleSignature
+		);
+		replayed.signature = fair.signature.clone();
+		replayed.seed = seed_for(0, false);
+		assert_noop!(
+			Chance::settle_fair_bet(Origin::signed(SETTLER), 0, replayed),
+			Error::<Test>::InvalidOracleSignature
+		);
+		assert_noop!(
+			Chance::settle_fair_bet(Origin::signed(2), 0, fair.clone()),
+			pallet_admin::Error::<Test>::NotSettler
+		);
+		let (bet, won) = Chance::settle_fair_bet(Origin::signed(SETTLER), 0, fair.clone()).unwrap();
+		assert!(won);
+		assert_eq!(bet.id, 0);
+		assert!(Chance::seed_index(Subject::Bet(0)).is_none());
+
+		let verification = Chance::verify_bet(0).unwrap();
+		assert_eq!(verification.verdict, Verdict::Won);
+		assert_eq!(verification.record.reveal, fair);
+		assert_eq!(verification.record.oracle, oracle().public());
+		assert_eq!(verification.record.block_hash, H256::repeat_byte(3));
+
+		let lost = reveal(Subject::Bet(1), seed_for(1, false));
+		assert_eq!(Chance::settle_fair_bet(Origin::signed(SETTLER), 1, lost).unwrap().1, false);
+		assert_eq!(Chance::verify_bet(1).unwrap().verdict, Verdict::Lost);
+		assert_eq!(Chance::bet_history(2)[1].outcome, BetOutcome::Lost);
+		assert!(Chance::verify_bet(2).is_none());
+	}
+)}
+
+#[test]
+fn test_fair_settlement_needs_the_oracle_key() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		close_cancellation_windows();
+		let fair = reveal(Subject::Bet(0), seed_for(0, true));
+
+		crate::OracleKey::kill();
+		assert_noop!(
+			Chance::settle_fair_bet(Origin::signed(SETTLER), 0, fair.clone()),
+			Error::<Test>::NoOracleKey
+		);
+		let other = sr25519::Pair::from_seed(&[8; 32]).public();
+		assert_noop!(Chance::set_oracle_key(Origin::signed(2), other), pallet_admin::Error::<Test>::MissingRole);
+		assert_ok!(Chance::set_oracle_key(Origin::root(), other));
+		assert_noop!(
+			Chance::settle_fair_bet(Origin::signed(SETTLER), 0, fair.clone()),
+			Error::<Test>::InvalidOracleSignature
+		);
+		assert_ok!(Chance::set_oracle_key(Origin::root(), oracle().public()));
+		assert!(Chance::settle_fair_bet(Origin::signed(SETTLER), 0, fair).unwrap().1);
+	}
+)}
+
+#[test]
+fn test_paused_settlement_is_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		close_cancellation_windows();
+		let fair = reveal(Subject::Bet(0), seed_for(0, true));
+
+		assert_ok!(Admin::pause(Origin::root(), Operation::Settle));
+		assert_noop!(
+			Chance::settle_fair_bet(Origin::signed(SETTLER), 0, fair.clone()),
+			Error::<Test>::SettlementPaused
+		);
+		assert_noop!(
+			Chance::scheduled_bet_callback(Origin::signed(SETTLER), 0, true),
+			Error::<Test>::SettlementPaused
+		);
+		assert_ok!(Admin::unpause(Origin::root(), Operation::Settle));
+		assert_ok!(Chance::settle_fair_bet(Origin::signed(SETTLER), 0, fair));
+	}
+)}
+
+#[test]
+fn test_trimmed_history_drops_fairness_records() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		for _ in 0..4 {
+			assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		}
+		close_cancellation_windows();
+		for id in 0..4 {
+			let fair = reveal(Subject::Bet(id), seed_for(id, false));
+			assert_ok!(Chance::settle_fair_bet(Origin::signed(SETTLER), id, fair));
+		}
+		// only the last `MaxBetHistory` bets keep their randomness
+		assert!(Chance::verify_bet(0).is_none());
+		assert!((1..4).all(|id| Chance::verify_bet(id).is_some()));
+	}
+)}
+
+fn limit(max_wager: Option<u128>, max_loss: Option<u128>) -> GamingLimit<u128> {
+	GamingLimit { max_wager, max_loss }
+}
+
+#[test]
+fn test_wager_limit_resets_each_period() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::set_gaming_limit(Origin::signed(2), DOT, LimitPeriod::Day, limit(Some(2500000000000), None)));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 1000000000000), Error::<Test>::WagerLimitExceeded);
+		// limits are per currency
+		assert_ok!(Pooler::deposit(Origin::signed(1), USD, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), USD, 1000000000000));
+
+		System::set_block_number(1 + BLOCKS_PER_DAY);
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+	}
+)}
+
+#[test]
+fn test_loss_limit_counts_winnings() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::set_gaming_limit(Origin::signed(2), DOT, LimitPeriod::Week, limit(None, Some(1500000000000))));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 1000000000000), Error::<Test>::LossLimitExceeded);
+
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 0, true));
+		assert_eq!(Chance::gaming_status(&2, DOT).periods[1].usage.loss(), 0);
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+	}
+)}
+
+#[test]
+fn test_raising_a_limit_waits_for_cooldown() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::set_gaming_limit(Origin::signed(2), DOT, LimitPeriod::Day, limit(Some(1000000000000), None)));
+		assert_ok!(Chance::set_gaming_limit(Origin::signed(2), DOT, LimitPeriod::Day, limit(Some(5000000000000), None)));
+		let status = Chance::gaming_status(&2, DOT);
+		assert_eq!(status.periods[0].limit, limit(Some(1000000000000), None));
+		assert_eq!(
+			status.periods[0].pending_increase,
+			Some((limit(Some(5000000000000), None), 1 + LIMIT_INCREASE_COOLDOWN))
+		);
+
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 1000000000000), Error::<Test>::WagerLimitExceeded);
+		System::set_block_number(1 + LIMIT_INCREASE_COOLDOWN);
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_eq!(Chance::gaming_limit(2, (DOT, LimitPeriod::Day)), limit(Some(5000000000000), None));
+	}
+)}
+
+#[test]
+fn test_self_excluded_player_cannot_bet() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::self_exclude(Origin::signed(2), 5));
+		// an exclusion can't be shortened
+		assert_ok!(Chance::self_exclude(Origin::signed(2), 1));
+		assert_eq!(Chance::gaming_status(&2, DOT).self_excluded_until, Some(6));
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 1000000000000), Error::<Test>::SelfExcluded);
+
+		System::set_block_number(6);
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+	}
+)}
+
+#[test]
+fn test_treasury_account_is_set_by_treasury_manager() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Chance::set_treasury_account(Origin::signed(8), 8), pallet_admin::Error::<Test>::MissingRole);
+		assert_ok!(Admin::grant_role(Origin::root(), Role::TreasuryManager, 8));
+		assert_ok!(Chance::set_treasury_account(Origin::signed(8), 8));
+
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_eq!(Tokens::free_balance(DOT, &8), 1000000000);
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 0);
+	}
+)}
+
+#[test]
+fn test_bet_limits_are_enforced() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		change_parameter(ParameterChange::BetLimits(1000000, 2000000000000, Perbill::from_percent(1)));
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 999999), Error::<Test>::BetBelowMinimum);
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 2000000000001), Error::<Test>::BetAboveMaximum);
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 1000000000001), Error::<Test>::BetExceedsPoolRatio);
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+	}
+)}
+
+#[test]
+fn test_zero_bets_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Admin::min_bet(), 1);
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 0), Error::<Test>::BetBelowMinimum);
+		assert_noop!(Chance::bet(Origin::signed(2), DOT, 1), Error::<Test>::NotEnoughLiquidity);
+		assert_noop!(
+			Admin::schedule_parameter_change(Origin::root(), ParameterChange::BetLimits(0, 1, Perbill::one())),
+			pallet_admin::Error::<Test>::InvalidBetLimits
+		);
+	}
+)}
+
+#[test]
+fn test_fee_parameters_are_bounded() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Admin::schedule_parameter_change(Origin::root(), ParameterChange::FeeMultiplier(MAX_FEE_MULTIPLIER + 1)),
+			pallet_admin::Error::<Test>::InvalidFeeMultiplier
+		);
+		assert_noop!(
+			Admin::schedule_parameter_change(Origin::root(), ParameterChange::SystemDecimals(MAX_SYSTEM_DECIMALS + 1)),
+			pallet_admin::Error::<Test>::InvalidSystemDecimals
+		);
+		assert_ok!(Admin::schedule_parameter_change(Origin::root(), ParameterChange::FeeMultiplier(MAX_FEE_MULTIPLIER)));
+		assert_ok!(Admin::schedule_parameter_change(Origin::root(), ParameterChange::SystemDecimals(MAX_SYSTEM_DECIMALS)));
+	}
+)}
+
+#[test]
+fn test_parameters_can_only_be_changed_by_root_or_parameter_manager() {
+	new_test_ext().execute_with(|| {
+		let limits = ParameterChange::BetLimits(1, 2, Perbill::one());
+		assert_noop!(
+			Admin::schedule_parameter_change(Origin::signed(SETTLER), limits.clone()),
+			pallet_admin::Error::<Test>::MissingRole
+		);
+		assert_ok!(Admin::grant_role(Origin::root(), Role::ParameterManager, 2));
+		assert_noop!(
+			Admin::schedule_parameter_change(Origin::signed(2), ParameterChange::BetLimits(2, 1, Perbill::one())),
+			pallet_admin::Error::<Test>::InvalidBetLimits
+		);
+		assert_ok!(Admin::schedule_parameter_change(Origin::signed(2), limits));
+		assert_noop!(Admin::enact_parameter_change(Origin::signed(2), 0), DispatchError::BadOrigin);
+	}
+)}
+
+#[test]
+fn test_parameter_changes_wait_for_delay() {
+	new_test_ext().execute_with(|| {
+		let change = ParameterChange::FeeMultiplier(20);
+		assert_ok!(Admin::schedule_parameter_change(Origin::root(), change.clone()));
+		assert_eq!(Admin::scheduled_parameter_change(0), Some((change, 1 + PARAMETER_CHANGE_DELAY)));
+
+		Scheduler::on_initialize(PARAMETER_CHANGE_DELAY);
+		assert_eq!(Admin::fee_multiplier(), 10);
+		Scheduler::on_initialize(1 + PARAMETER_CHANGE_DELAY);
+		assert_eq!(Admin::fee_multiplier(), 20);
+		assert_eq!(Admin::scheduled_parameter_change(0), None);
+
+		// the fee doubles with the multiplier
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 2000000000);
+	}
+)}
+
+#[test]
+fn test_parameter_changes_can_be_cancelled() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Admin::schedule_parameter_change(Origin::root(), ParameterChange::MaxSettlers(1)));
+		assert_ok!(Admin::cancel_parameter_change(Origin::root(), 0));
+		assert_noop!(
+			Admin::cancel_parameter_change(Origin::root(), 0),
+			pallet_admin::Error::<Test>::UnknownParameterChange
+		);
+		Scheduler::on_initialize(1 + PARAMETER_CHANGE_DELAY);
+		assert_eq!(Admin::role_config(Role::Settler).max_members, 10);
+
+		change_parameter(ParameterChange::MaxSettlers(1));
+		assert_eq!(Admin::role_config(Role::Settler).max_members, 1);
+		assert_noop!(Admin::add_setter(Origin::root(), 8), pallet_admin::Error::<Test>::SettlerLimit);
+	}
+)}
+
+#[test]
+fn test_roles_are_managed_by_admin_origin_or_manager_role() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Admin::grant_role(Origin::signed(1), Role::Guardian, 2), DispatchError::BadOrigin);
+		assert_noop!(Admin::pause(Origin::signed(2), Operation::Bet), pallet_admin::Error::<Test>::MissingRole);
+		assert_noop!(
+			Admin::grant_role(Origin::root(), Role::Settler, 2),
+			pallet_admin::Error::<Test>::SettlerRole
+		);
+
+		assert_ok!(Admin::set_role_config(Origin::root(), Role::Guardian, 2, Some(Role::ParameterManager)));
+		assert_ok!(Admin::grant_role(Origin::root(), Role::ParameterManager, 1));
+		assert_ok!(Admin::grant_role(Origin::signed(1), Role::Guardian, 2));
+		assert_ok!(Admin::grant_role(Origin::signed(1), Role::Guardian, 3));
+		assert_noop!(
+			Admin::grant_role(Origin::signed(1), Role::Guardian, 4),
+			pallet_admin::Error::<Test>::RoleLimit
+		);
+		assert_eq!(Admin::role_members(Role::Guardian), vec![2, 3]);
+
+		assert_ok!(Admin::pause(Origin::signed(2), Operation::Bet));
+		assert!(Admin::is_paused(Operation::Bet));
+		assert_noop!(Admin::unpause(Origin::signed(2), Operation::Bet), DispatchError::BadOrigin);
+
+		assert_ok!(Admin::revoke_role(Origin::signed(1), Role::Guardian, 2));
+		assert!(!Admin::has_role(&2, Role::Guardian));
+		assert!(Admin::has_role(&SETTLER, Role::Settler));
+	}
+)}
+
+#[test]
+fn test_circuit_breaker_trips_on_large_pool_loss() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 10000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), 0, true));
+		assert!(!Admin::is_paused(Operation::Bet));
+
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 8000000000000));
+		let bet = Chance::scheduled_bet()[0].clone();
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), bet.id, true));
+		assert!(Admin::is_paused(Operation::Bet));
+		assert!(Admin::is_paused(Operation::Settle));
+		assert!(!Admin::is_paused(Operation::Deposit));
+	}
+)}
+
+#[test]
+fn test_circuit_breaker_window_resets() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 10000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 3000000000000));
+		let bet = Chance::scheduled_bet()[0].clone();
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), bet.id, true));
+		assert_eq!(Chance::loss_window(DOT).losses, bet.wager);
+
+		System::set_block_number(11);
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		let bet = Chance::scheduled_bet()[0].clone();
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), bet.id, true));
+		assert_eq!(Chance::loss_window(DOT).start, 11);
+		assert_eq!(Chance::loss_window(DOT).losses, bet.wager);
+		assert!(!Admin::is_paused(Operation::Bet));
+	}
+)}
+
+#[test]
+fn test_settlers_are_managed_by_admin_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Admin::add_setter(Origin::signed(SETTLER), 8), sp_runtime::DispatchError::BadOrigin);
+		assert_ok!(Admin::add_setter(Origin::root(), 8));
+		assert_noop!(Admin::remove_settler(Origin::signed(8), SETTLER), sp_runtime::DispatchError::BadOrigin);
+
+		// settlers can't schedule each other's removal
+		assert_noop!(
+			Admin::schedule_settler_removal(Origin::signed(8), SETTLER),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Admin::schedule_settler_removal(Origin::root(), SETTLER));
+		assert_noop!(
+			Admin::schedule_settler_removal(Origin::root(), SETTLER),
+			pallet_admin::Error::<Test>::RemovalAlreadyScheduled
+		);
+		assert_ok!(Admin::cancel_settler_removal(Origin::root(), SETTLER));
+		assert!(Admin::removals_at(6).is_empty());
+		Admin::on_initialize(6);
+		assert!(Admin::is_settler(&SETTLER));
+
+		assert_ok!(Admin::schedule_settler_removal(Origin::root(), SETTLER));
+		assert_eq!(Admin::removals_at(6), vec![SETTLER]);
+		Admin::on_initialize(5);
+		assert!(Admin::is_settler(&SETTLER));
+		Admin::on_initialize(6);
+		assert!(!Admin::is_settler(&SETTLER));
+		assert_eq!(Admin::pending_removal(SETTLER), None);
+	}
+)}
+
+#[test]
+fn test_adding_settler_reserves_bond() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Admin::add_setter(Origin::root(), 5), pallet_admin::Error::<Test>::InsufficientBond);
+		assert_ok!(Admin::add_setter(Origin::root(), 8));
+		assert_eq!(Balances::reserved_balance(8), SETTLER_BOND);
+		assert_eq!(Admin::bond(8), SETTLER_BOND);
+
+		assert_ok!(Admin::slash_settler(Origin::root(), 8, 30));
+		assert_eq!(Balances::reserved_balance(8), SETTLER_BOND - 30);
+		assert_eq!(Admin::bond(8), SETTLER_BOND - 30);
+
+		assert_ok!(Admin::remove_settler(Origin::root(), 8));
+		assert_eq!(Balances::reserved_balance(8), 0);
+		assert_eq!(Balances::free_balance(8), 1000 - 30);
+	}
+)}
+
+#[test]
+fn test_genesis_settlers_are_bonded() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Balances::reserved_balance(SETTLER), SETTLER_BOND);
+		assert_eq!(Admin::bond(SETTLER), SETTLER_BOND);
+	}
+)}
+
+#[test]
+fn test_late_settlements_slash_settlers_at_era_end() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Admin::add_setter(Origin::root(), 8));
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		for _ in 0..2 {
+			assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		}
+		System::set_block_number(1 + SETTLEMENT_DEADLINE + 1);
+		for _ in 0..2 {
+			let bet = Chance::scheduled_bet()[0].clone();
+			assert_ok!(Chance::scheduled_bet_callback(Origin::signed(8), bet.id, false));
+		}
+		assert_eq!(Admin::missed_settlements(8), 2);
+		assert_eq!(Admin::missed_settlements(SETTLER), 0);
+
+		Admin::on_initialize(ERA_LENGTH);
+		assert_eq!(Admin::missed_settlements(8), 0);
+		// 10% of the late settler's bond is slashed, and only of its bond
+		assert_eq!(Admin::bond(8), SETTLER_BOND - 10);
+		assert_eq!(Admin::bond(SETTLER), SETTLER_BOND);
+	}
+)}
+
+#[test]
+fn test_settler_performance_is_tracked() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		assert_ok!(Chance::bet(Origin::signed(2), DOT, 1000000000000));
+		System::set_block_number(3);
+		let bet = Chance::scheduled_bet()[0].clone();
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), bet.id, false));
+		System::set_block_number(5);
+		let bet = Chance::scheduled_bet()[0].clone();
+		assert_ok!(Chance::scheduled_bet_callback(Origin::signed(SETTLER), bet.id, false));
+
+		let stats = Admin::settler_stats(SETTLER);
+		assert_eq!(stats.settled, 2);
+		assert_eq!(stats.total_latency, 6);
+		assert_eq!(stats.average_latency(), Some(3));
+
+		assert_ok!(Admin::dispute_settlement(Origin::root(), SETTLER));
+		assert_eq!(Admin::settler_stats(SETTLER).disputes, 1);
+	}
+)}
+
+#[test]
+fn test_underperforming_settlers_are_rotated_out() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Admin::add_setter(Origin::root(), 8));
+		for _ in 0..3 {
+			assert_ok!(Admin::dispute_settlement(Origin::root(), 8));
+		}
+		for _ in 0..3 {
+			assert_ok!(Admin::dispute_settlement(Origin::root(), SETTLER));
+		}
+		Admin::on_initialize(ERA_LENGTH);
+		// the last settler is never dropped
+		assert_eq!(Admin::settlers(), vec![8]);
+		assert_eq!(Admin::bond(8), SETTLER_BOND);
+	}
+)}
+
+#[test]
+fn test_migrating_v1_turns_scheduled_pairs_into_pending_bets() {
+	new_test_ext().execute_with(|| {
+		use crate::{migrations::{self, Releases}, NextBetId, StorageVersion};
+		use frame_support::{storage::migration::put_storage_value, StorageValue};
+
+		StorageVersion::put(Releases::V1);
+		put_storage_value(b"Chance", b"ScheduledBet", &[], vec![(1u64, 100u128), (2, 50)]);
+		NextBetId::put(5);
+		System::set_block_number(1 + CANCELLATION_WINDOW);
+
+		let snapshot = migrations::pre_migrate::<Test>().unwrap();
+		migrations::migrate::<Test>();
+		assert_ok!(migrations::post_migrate::<Test>(snapshot));
+
+		assert_eq!(Chance::scheduled_bet(), vec![pending(5, 1, DOT, 100, 0), pending(6, 2, DOT, 50, 0)]);
+		assert_eq!(Chance::next_bet_id(), 7);
+		assert_eq!(Chance::seed_index(Subject::Bet(5)), Some(0));
+		assert_eq!(Chance::seed_index(Subject::Bet(6)), Some(1));
+		assert!(!Chance::is_cancellable(&Chance::scheduled_bet()[0]));
+		assert_eq!(StorageVersion::get(), Releases::V2);
+		// Migrating again is a no-op.
+		assert_eq!(migrations::migrate::<Test>(), 0);
+		assert_eq!(Chance::scheduled_bet().len(), 2);
+	});
+}
+
+#[test]
+fn test_genesis_config_sets_parameters_treasury_and_pauses() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> {
+		parameters: vec![ParameterChange::FeeMultiplier(20), ParameterChange::BetLimits(5, 500, Perbill::from_percent(10))],
+		treasury: Some(8),
+		paused: vec![Operation::Bet],
+		oracle_key: Some(oracle().public()),
+	}.assimilate_storage(&mut t).unwrap();
+	sp_io::TestExternalities::new(t).execute_with(|| {
+		assert_eq!(Admin::fee_multiplier(), 20);
+		assert_eq!(Admin::min_bet(), 5);
+		assert_eq!(Admin::max_bet(), 500);
+		assert_eq!(Admin::max_bet_pool_ratio(), Perbill::from_percent(10));
+		assert_eq!(Chance::treasury_override(), Some(8));
+		assert!(Admin::is_paused(Operation::Bet));
+		assert!(!Admin::is_paused(Operation::Settle));
+		assert_eq!(Chance::oracle_key(), Some(oracle().public()));
+	});
+}
@@ -0,0 +1,216 @@
+//! A periodic lottery played against the chance pool.
+//!
+//! Tickets are sold for `RoundLength` blocks. `JackpotCut` of every ticket goes to a progressive
+//! jackpot held by this pallet and the rest goes to the LP pool as the house edge. When a round
+//! closes it is assigned an oracle seed index, and once the closing block is built a settler
+//! submits the seed the oracle signed for it. One ticket per prize tier is drawn on chain with
+//! `pallet_chance_fairness::draw_distinct` from the seed mixed with the closing block's hash.
+//! Whatever share of the jackpot isn't won rolls over to
+//! the next round. Tickets count towards the buyer's gaming limits like bets.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure,
+	traits::Get, transactional, weights::Weight,
+};
+use frame_system::{self as system, ensure_signed};
+use orml_traits::MultiCurrency;
+use pallet_admin as admin;
+use pallet_chance::{self as chance, BalanceOf, CurrencyIdOf, Reveal, Subject};
+use pallet_chance_fairness as fairness;
+use sp_runtime::{
+	traits::{AccountIdConversion, Saturating, Zero},
+	ModuleId, Perbill,
+};
+use sp_std::prelude::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub type RoundIndex = u32;
+
+pub trait Trait: system::Trait + chance::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// The number of blocks tickets are sold for in each round. Zero stops rounds closing.
+	type RoundLength: Get<Self::BlockNumber>;
+	/// The currency tickets are bought and prizes are paid in.
+	type LotteryCurrency: Get<CurrencyIdOf<Self>>;
+	type TicketPrice: Get<BalanceOf<Self>>;
+	/// The share of each ticket added to the jackpot. The rest goes to the LP pool.
+	type JackpotCut: Get<Perbill>;
+	/// The share of the jackpot won by each prize tier, first prize first. The shares should add
+	/// up to at most one; the remainder rolls over.
+	type PrizeTiers: Get<Vec<Perbill>>;
+	type MaxTicketsPerRound: Get<u32>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Lottery {
+		/// The round tickets are currently sold for.
+		CurrentRound get(fn current_round): RoundIndex;
+		/// The owner of each ticket of a round, by ticket number.
+		Tickets get(fn tickets): map hasher(twox_64_concat) RoundIndex => Vec<T::AccountId>;
+		/// Closed rounds waiting for the offchain worker to draw their winners.
+		PendingDraws get(fn pending_draws): Vec<RoundIndex>;
+		/// The block each round waiting to be drawn closed in.
+		ClosedAt get(fn closed_at): map hasher(twox_64_concat) RoundIndex => Option<T::BlockNumber>;
+		/// The prizes paid out in each drawn round, first prize first.
+		Winners get(fn winners): map hasher(twox_64_concat) RoundIndex => Vec<(T::AccountId, BalanceOf<T>)>;
+		/// The oracle seed each round was drawn with and the hash of the block it closed in, so
+		/// anyone can redraw it.
+		DrawSeeds get(fn draw_seed): map hasher(twox_64_concat) RoundIndex => Option<(Reveal, T::Hash)>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		AccountId = <T as system::Trait>::AccountId,
+		Balance = BalanceOf<T>,
+	{
+		/// \[who, round, count\]
+		TicketsBought(AccountId, RoundIndex, u32),
+		/// Ticket sales for a round ended. \[round, tickets_sold\]
+		RoundClosed(RoundIndex, u32),
+		/// \[round, winner, prize\]
+		PrizeWon(RoundIndex, AccountId, Balance),
+	}
+);
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// At least one ticket must be bought.
+		NoTickets,
+		/// The round has sold all its tickets.
+		RoundFull,
+		/// The round isn't waiting to be drawn.
+		UnknownDraw,
+		/// The block the round closed in hasn't been built yet.
+		DrawNotReady,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		const RoundLength: T::BlockNumber = T::RoundLength::get();
+
+		const TicketPrice: BalanceOf<T> = T::TicketPrice::get();
+
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			// A round length of zero stops rounds closing.
+			let round_length = T::RoundLength::get();
+			if !round_length.is_zero() && (now % round_length).is_zero() {
+				Self::close_round(now);
+				return T::DbWeight::get().reads_writes(4, 6);
+			}
+			0
+		}
+
+		/// Buy `count` tickets for the current round.
+		#[weight = 0]
+		pub fn buy_tickets(origin, count: u32) {
+			let who = ensure_signed(origin)?;
+			ensure!(count > 0, Error::<T>::NoTickets);
+			let round = Self::current_round();
+			let sold = Self::tickets_sold(round);
+			ensure!(sold.saturating_add(count) <= T::MaxTicketsPerRound::get(), Error::<T>::RoundFull);
+
+			let currency_id = T::LotteryCurrency::get();
+			let price = T::TicketPrice::get().saturating_mul(count.into());
+			let to_jackpot = T::JackpotCut::get() * price;
+			<chance::Module<T>>::ensure_within_gaming_limits(&who, currency_id, price)?;
+			<T as chance::Trait>::Currency::ensure_can_withdraw(currency_id, &who, price)?;
+			<T as chance::Trait>::Currency::transfer(currency_id, &who, &Self::account_id(), to_jackpot)?;
+			<T as chance::Trait>::Currency::transfer(currency_id, &who, &<chance::Module<T>>::account_id(), price - to_jackpot)?;
+			<chance::Module<T>>::note_gaming_stake(&who, currency_id, price);
+
+			Tickets::<T>::mutate(round, |tickets| {
+				tickets.extend(sp_std::iter::repeat(who.clone()).take(count as usize))
+			});
+			Self::deposit_event(RawEvent::TicketsBought(who, round, count));
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The account holding the jackpot.
+	pub fn account_id() -> T::AccountId {
+		ModuleId(*b"py/lotto").into_account()
+	}
+
+	/// The current jackpot.
+	pub fn jackpot() -> BalanceOf<T> {
+		<T as chance::Trait>::Currency::free_balance(T::LotteryCurrency::get(), &Self::account_id())
+	}
+
+	pub fn tickets_sold(round: RoundIndex) -> u32 {
+		Tickets::<T>::decode_len(round).unwrap_or(0) as u32
+	}
+
+	/// The number of tickets to draw for `round`.
+	pub fn picks_needed(round: RoundIndex) -> u32 {
+		(T::PrizeTiers::get().len() as u32).min(Self::tickets_sold(round))
+	}
+
+	/// The tickets of `round` drawn from `seed` and the hash of the block the round closed in,
+	/// first prize first.
+	pub fn picks(round: RoundIndex, seed: &[u8], block_hash: &[u8]) -> Vec<u32> {
+		let seed = fairness::round_seed(seed, block_hash);
+		fairness::draw_distinct(&seed, Subject::Lottery(round), Self::picks_needed(round), Self::tickets_sold(round))
+	}
+
+	/// Draw `round` from the seed the oracle signed for it and pay the prizes to the owners of
+	/// the drawn tickets.
+	#[transactional]
+	pub fn draw_callback(origin: T::Origin, round: RoundIndex, reveal: Reveal) -> DispatchResult {
+		<admin::Module<T>>::ensure_settler(origin)?;
+		ensure!(!<admin::Module<T>>::is_paused(admin::Operation::Settle), chance::Error::<T>::SettlementPaused);
+		let pos = Self::pending_draws().iter().position(|r| *r == round).ok_or(Error::<T>::UnknownDraw)?;
+		let closed_at = Self::closed_at(round).ok_or(Error::<T>::UnknownDraw)?;
+		ensure!(<system::Module<T>>::block_number() > closed_at, Error::<T>::DrawNotReady);
+		<chance::Module<T>>::check_reveal(Subject::Lottery(round), &reveal)?;
+		let block_hash = <system::Module<T>>::block_hash(closed_at);
+		let tickets = Self::tickets(round);
+		let picks = Self::picks(round, &reveal.seed, block_hash.as_ref());
+
+		PendingDraws::mutate(|draws| {
+			draws.remove(pos);
+		});
+		let currency_id = T::LotteryCurrency::get();
+		let jackpot = Self::jackpot();
+		let mut winners = Vec::with_capacity(picks.len());
+		for (pick, share) in picks.iter().zip(T::PrizeTiers::get()) {
+			let winner = tickets[*pick as usize].clone();
+			let prize = share * jackpot;
+			<T as chance::Trait>::Currency::transfer(currency_id, &Self::account_id(), &winner, prize)?;
+			<chance::Module<T>>::note_gaming_return(&winner, currency_id, prize);
+			Self::deposit_event(RawEvent::PrizeWon(round, winner.clone(), prize));
+			winners.push((winner, prize));
+		}
+		Winners::<T>::insert(round, winners);
+		Tickets::<T>::remove(round);
+		ClosedAt::<T>::remove(round);
+		DrawSeeds::<T>::insert(round, (reveal, block_hash));
+		<chance::Module<T>>::release_seed_index(Subject::Lottery(round));
+		Ok(())
+	}
+
+	fn close_round(now: T::BlockNumber) {
+		let round = Self::current_round();
+		CurrentRound::put(round.wrapping_add(1));
+		let sold = Self::tickets_sold(round);
+		if sold > 0 {
+			<chance::Module<T>>::assign_seed_index(Subject::Lottery(round));
+			ClosedAt::<T>::insert(round, now);
+			PendingDraws::mutate(|draws| draws.push(round));
+		}
+		Self::deposit_event(RawEvent::RoundClosed(round, sold));
+	}
+}
@@ -0,0 +1,149 @@
+use crate::{Error, RoundIndex, mock::*};
+use frame_support::{assert_ok, assert_noop, traits::OnInitialize};
+use orml_traits::MultiCurrency;
+use pallet_admin::Operation;
+use pallet_chance::{GamingLimit, LimitPeriod, Reveal, Subject};
+use sp_core::{Pair, H256};
+
+/// The test oracle's reveal of `seed` for lottery round `round`.
+fn reveal(round: RoundIndex, seed: Vec<u8>) -> Reveal {
+	let subject = Subject::Lottery(round);
+	let index = Chance::seed_index(subject).unwrap();
+	let signature = oracle().sign(&pallet_chance_fairness::signed_message(subject, index, &seed));
+	Reveal { index, seed, signature: signature.0.to_vec() }
+}
+
+/// The hash given to the block `round` closed in.
+fn closing_hash(round: RoundIndex) -> H256 {
+	H256::repeat_byte(round as u8 + 1)
+}
+
+/// Build the block `round` closed in and move past it.
+fn build_closing_block(round: RoundIndex) {
+	let closed_at = Lottery::closed_at(round).unwrap();
+	frame_system::BlockHash::<Test>::insert(closed_at, closing_hash(round));
+	System::set_block_number(closed_at + 1);
+}
+
+/// The test oracle's reveal of a seed drawing `picks` in `round`.
+fn reveal_drawing(round: RoundIndex, picks: &[u32]) -> Reveal {
+	let seed = (0u8..).map(|i| vec![i; 32])
+		.find(|seed| Lottery::picks(round, seed, closing_hash(round).as_ref()) == picks)
+		.unwrap();
+	reveal(round, seed)
+}
+
+#[test]
+fn test_ticket_price_is_split_between_jackpot_and_pool() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Lottery::buy_tickets(Origin::signed(2), 0), Error::<Test>::NoTickets);
+		assert_ok!(Lottery::buy_tickets(Origin::signed(2), 2));
+		assert_eq!(Lottery::tickets(0), vec![2, 2]);
+		assert_eq!(Lottery::jackpot(), 1600);
+		assert_eq!(Tokens::free_balance(DOT, &Chance::account_id()), 400);
+		assert_eq!(Tokens::free_balance(DOT, &2), 1000000000000000 - 2 * TICKET_PRICE);
+
+		assert_ok!(Lottery::buy_tickets(Origin::signed(1), 3));
+		assert_noop!(Lottery::buy_tickets(Origin::signed(1), 1), Error::<Test>::RoundFull);
+		// 3 can't afford a ticket and pays nothing
+		assert!(Lottery::buy_tickets(Origin::signed(3), 1).is_err());
+		assert_eq!(Tokens::free_balance(DOT, &3), 10);
+	}
+)}
+
+#[test]
+fn test_tickets_count_towards_gaming_limits() {
+	new_test_ext().execute_with(|| {
+		let limit = GamingLimit { max_wager: Some(3 * TICKET_PRICE), max_loss: None };
+		assert_ok!(Chance::set_gaming_limit(Origin::signed(2), DOT, LimitPeriod::Day, limit));
+		assert_ok!(Lottery::buy_tickets(Origin::signed(2), 2));
+		assert_noop!(Lottery::buy_tickets(Origin::signed(2), 2), pallet_chance::Error::<Test>::WagerLimitExceeded);
+		assert_ok!(Lottery::buy_tickets(Origin::signed(2), 1));
+
+		assert_ok!(Chance::self_exclude(Origin::signed(1), 10));
+		assert_noop!(Lottery::buy_tickets(Origin::signed(1), 1), pallet_chance::Error::<Test>::SelfExcluded);
+	}
+)}
+
+#[test]
+fn test_closed_round_pays_tiered_prizes() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::buy_tickets(Origin::signed(1), 1));
+		assert_ok!(Lottery::buy_tickets(Origin::signed(2), 2));
+		Lottery::on_initialize(ROUND_LENGTH);
+		assert_eq!(Lottery::current_round(), 1);
+		assert_eq!(Lottery::pending_draws(), vec![0]);
+		assert_eq!(Lottery::closed_at(0), Some(ROUND_LENGTH));
+		assert_eq!(Lottery::picks_needed(0), 2);
+		assert_eq!(Chance::seed_index(Subject::Lottery(0)), Some(0));
+
+		// the round can't be drawn before the block it closed in is built
+		System::set_block_number(ROUND_LENGTH);
+		assert_noop!(
+			Lottery::draw_callback(Origin::signed(SETTLER), 0, reveal(0, vec![0; 32])),
+			Error::<Test>::DrawNotReady
+		);
+		build_closing_block(0);
+		let drawn = reveal_drawing(0, &[1, 0]);
+		assert_noop!(Lottery::draw_callback(Origin::signed(2), 0, drawn.clone()), pallet_admin::Error::<Test>::NotSettler);
+		assert_noop!(Lottery::draw_callback(Origin::signed(SETTLER), 1, drawn.clone()), Error::<Test>::UnknownDraw);
+		// the seed must be the one the oracle signed for the round
+		let mut forged = drawn.clone();
+		forged.seed = reveal_drawing(0, &[2, 1]).seed;
+		assert_noop!(
+			Lottery::draw_callback(Origin::signed(SETTLER), 0, forged),
+			pallet_chance::Error::<Test>::InvalidOracleSignature
+		);
+
+		assert_ok!(Admin::pause(Origin::root(), Operation::Settle));
+		assert_noop!(
+			Lottery::draw_callback(Origin::signed(SETTLER), 0, drawn.clone()),
+			pallet_chance::Error::<Test>::SettlementPaused
+		);
+		assert_ok!(Admin::unpause(Origin::root(), Operation::Settle));
+
+		let before = Tokens::free_balance(DOT, &2);
+		assert_ok!(Lottery::draw_callback(Origin::signed(SETTLER), 0, drawn.clone()));
+		// the jackpot of 2400 pays 50% and 20%; the rest rolls over
+		assert_eq!(Lottery::winners(0), vec![(2, 1200), (1, 480)]);
+		assert_eq!(Tokens::free_balance(DOT, &2), before + 1200);
+		assert_eq!(Lottery::jackpot(), 720);
+		assert!(Lottery::pending_draws().is_empty());
+		assert!(Lottery::tickets(0).is_empty());
+		assert_eq!(Lottery::draw_seed(0), Some((drawn, closing_hash(0))));
+		assert!(Lottery::closed_at(0).is_none());
+		assert!(Chance::seed_index(Subject::Lottery(0)).is_none());
+	}
+)}
+
+#[test]
+fn test_draws_pick_distinct_tickets() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Lottery::buy_tickets(Origin::signed(1), 2));
+		for seed in 0u8..50 {
+			let picks = Lottery::picks(0, &[seed; 32], &[0; 32]);
+			assert_eq!(picks.len(), 2);
+			assert_ne!(picks[0], picks[1]);
+			assert!(picks.iter().all(|pick| *pick < 2));
+		}
+	}
+)}
+
+#[test]
+fn test_round_without_tickets_rolls_jackpot_over() {
+	new_test_ext().execute_with(|| {
+		Lottery::on_initialize(ROUND_LENGTH);
+		assert_eq!(Lottery::current_round(), 1);
+		assert!(Lottery::pending_draws().is_empty());
+		assert!(Chance::seed_index(Subject::Lottery(0)).is_none());
+
+		assert_ok!(Lottery::buy_tickets(Origin::signed(1), 1));
+		assert_eq!(Lottery::tickets(1), vec![1]);
+		Lottery::on_initialize(2 * ROUND_LENGTH);
+		// a single ticket only wins the first prize
+		assert_eq!(Lottery::picks_needed(1), 1);
+		build_closing_block(1);
+		assert_ok!(Lottery::draw_callback(Origin::signed(SETTLER), 1, reveal(1, vec![0; 32])));
+		assert_eq!(Lottery::jackpot(), 400);
+	}
+)}
@@ -1,7 +1,9 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::{
-	debug, decl_error, decl_event, decl_module, dispatch::DispatchResult, traits::Get, weights::Pays,
+	debug, decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult,
+	traits::{ChangeMembers, Get}, weights::Pays,
 };
 
 use frame_system::{
@@ -10,21 +12,73 @@ use frame_system::{
 		AppCrypto, CreateSignedTransaction, SendSignedTransaction, Signer,
 	},
 };
+use alt_serde::Deserialize;
 use sp_core::crypto::KeyTypeId;
 use sp_runtime::{
 	offchain as rt_offchain,
 	offchain::storage::StorageValueRef,
 	transaction_validity::TransactionPriority,
+	RuntimeDebug,
 };
 use sp_std::prelude::*;
 use sp_std::str;
-use chance::BalanceOf;
+use chance::{BalanceOf, BetId, CurrencyIdOf, PendingBetOf, Reveal, Subject};
+use admin::Role;
+use lottery::RoundIndex;
+use prediction::RoundId;
 
 #[cfg(test)]
 mod tests;
 
 pub const HTTP_REMOTE_REQUEST_STRING: &str = "http://localhost:3000/random";
 pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"ocwc");
+/// The persistent offchain storage key of the oracle request statistics, read by the node's
+/// metrics.
+pub const FETCH_STATS_KEY: &[u8] = b"ocw-control::fetch-stats";
+
+/// Running statistics of the offchain worker's oracle requests.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct FetchStats {
+	/// The number of requests made.
+	pub fetches: u64,
+	/// The number of requests that failed or got an unexpected response.
+	pub errors: u64,
+	/// How long the last request took, in milliseconds.
+	pub last_latency_ms: u64,
+}
+
+/// The oracle's answer for a subject: the hex seed at the requested index and the oracle's hex
+/// signature of it.
+#[derive(Deserialize)]
+#[serde(crate = "alt_serde")]
+struct OracleSeed<'a> {
+	seed: &'a str,
+	index: u64,
+	signature: &'a str,
+}
+
+/// Decode a hex string, with or without a `0x` prefix.
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+	let s = s.strip_prefix("0x").unwrap_or(s).as_bytes();
+	if s.len() % 2 != 0 {
+		return None;
+	}
+	s.chunks(2)
+		.map(|pair| {
+			let digit = |c: u8| (c as char).to_digit(16);
+			Some((digit(pair[0])? * 16 + digit(pair[1])?) as u8)
+		})
+		.collect()
+}
+
+/// Append the decimal digits of `n` to `buf`.
+fn append_decimal<N: Into<u64>>(buf: &mut Vec<u8>, n: N) {
+	let n = n.into();
+	if n >= 10 {
+		append_decimal(buf, n / 10);
+	}
+	buf.push(b'0' + (n % 10) as u8);
+}
 
 
 pub mod crypto {
@@ -57,7 +111,7 @@ pub mod crypto {
 }
 
 /// This is the pallet's configuration trait
-pub trait Trait: system::Trait + CreateSignedTransaction<Call<Self>> + chance::Trait + admin::Trait {
+pub trait Trait: system::Trait + CreateSignedTransaction<Call<Self>> + chance::Trait + admin::Trait + lottery::Trait + prediction::Trait {
 	/// The identifier type for an offchain worker.
 	type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
 	/// The overarching dispatch call type.
@@ -68,15 +122,31 @@ pub trait Trait: system::Trait + CreateSignedTransaction<Call<Self>> + chance::T
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 }
 
+decl_storage! {
+	trait Store for Module<T: Trait> as OcwControl {
+		/// The settler set as last reported by pallet_admin.
+		ActiveSettlers get(fn active_settlers): Vec<T::AccountId>;
+		/// The oracle URL. Empty means `HTTP_REMOTE_REQUEST_STRING`.
+		OracleEndpoint get(fn oracle_endpoint): Vec<u8>;
+	}
+}
+
 decl_event!(
 	/// Events generated by the module.
 	pub enum Event<T>
 	where
 		AccountId = <T as system::Trait>::AccountId,
+		CurrencyId = CurrencyIdOf<T>,
 		Balance = BalanceOf<T>,
 	{
-		BetWon(AccountId, Balance),
-		BetLost(AccountId, Balance),
+		/// \[bet_id, better, currency_id, wager\]
+		BetWon(BetId, AccountId, CurrencyId, Balance),
+		/// \[bet_id, better, currency_id, wager\]
+		BetLost(BetId, AccountId, CurrencyId, Balance),
+		/// The settler set changed. \[incoming, outgoing\]
+		SettlersChanged(Vec<AccountId>, Vec<AccountId>),
+		/// The oracle endpoint changed. \[endpoint\]
+		OracleEndpointUpdated(Vec<u8>),
 	}
 );
 
@@ -95,45 +165,89 @@ decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
 		fn deposit_event() = default;
 
+		/// Settle a bet with the seed the oracle signed for it. The outcome is derived on chain so
+		/// it can be verified later; see pallet_chance_fairness.
 		#[weight = (0, Pays::No)]
-		pub fn submit_signed(origin, better: T::AccountId, wager: BalanceOf<T>, did_win: bool) -> DispatchResult {
-			<admin::Module<T>>::ensure_settler(origin.clone())?;
-			debug::info!("Entering submit_signed. {:#?}, {:#?}, {:#?}", better, wager, did_win);
-
-			<chance::Module<T>>::scheduled_bet_callback(origin, better.clone(), wager, did_win)?;
-			if did_win {
-				Self::deposit_event(RawEvent::BetWon(better, wager));
+		pub fn submit_fair(origin, bet_id: BetId, reveal: Reveal) -> DispatchResult {
+			debug::info!("Entering submit_fair. {:#?}", bet_id);
+			let (bet, won) = <chance::Module<T>>::settle_fair_bet(origin, bet_id, reveal)?;
+			if won {
+				Self::deposit_event(RawEvent::BetWon(bet_id, bet.better, bet.currency_id, bet.wager));
 			} else {
-				Self::deposit_event(RawEvent::BetLost(better, wager));
+				Self::deposit_event(RawEvent::BetLost(bet_id, bet.better, bet.currency_id, bet.wager));
 			}
-
 			Ok(())
 		}
 
+		/// Draw a closed lottery round with the seed the oracle signed for it.
+		#[weight = (0, Pays::No)]
+		pub fn submit_draw(origin, round: RoundIndex, reveal: Reveal) -> DispatchResult {
+			debug::info!("Entering submit_draw. {:#?}", round);
+			<lottery::Module<T>>::draw_callback(origin, round, reveal)
+		}
+
+		/// Resolve a closed prediction round with the seed the oracle signed for it.
+		#[weight = (0, Pays::No)]
+		pub fn submit_resolution(origin, round_id: RoundId, reveal: Reveal) -> DispatchResult {
+			debug::info!("Entering submit_resolution. {:#?}", round_id);
+			<prediction::Module<T>>::resolve_callback(origin, round_id, reveal)
+		}
+
+		/// Point the offchain worker at another oracle. Needs root or an `OracleManager`.
+		#[weight = 0]
+		pub fn set_oracle_endpoint(origin, endpoint: Vec<u8>) {
+			<admin::Module<T>>::ensure_root_or_role(origin, Role::OracleManager)?;
+			OracleEndpoint::put(&endpoint);
+			Self::deposit_event(RawEvent::OracleEndpointUpdated(endpoint));
+		}
+
 		fn offchain_worker(block_number: T::BlockNumber) {
             let pending_bets = <chance::Module<T>>::scheduled_bet();
             debug::info!("Entering offchain worker");
+			if Self::active_settlers().is_empty() {
+				debug::warn!("No active settlers; skipping settlement");
+				return;
+			}
 			if pending_bets.len() > 0 {
 				debug::info!("Entering action");
 				for bet in pending_bets {
-                    let better = bet.0.clone();
-                    let wager = bet.1.clone();
-                    debug::info!("better {:#?}", better);
-                    debug::info!("bet {:#?}", wager);
+					// the better may still cancel, so don't request randomness yet
+					if <chance::Module<T>>::is_cancellable(&bet) {
+						continue;
+					}
+                    debug::info!("better {:#?}", bet.better);
+                    debug::info!("bet {:#?}", bet.wager);
 					let _ = Self::fetch_if_needed(bet);
 				}
 			}
+			for round in <lottery::Module<T>>::pending_draws() {
+				if let Err(e) = Self::draw(round) {
+					debug::error!("Drawing lottery round {} failed: {:?}", round, e);
+				}
+			}
+			for round_id in <prediction::Module<T>>::pending_resolutions() {
+				if let Err(e) = Self::resolve(round_id) {
+					debug::error!("Resolving prediction round {} failed: {:?}", round_id, e);
+				}
+			}
 		}
 	}
 }
 
+impl<T: Trait> ChangeMembers<T::AccountId> for Module<T> {
+	fn change_members_sorted(incoming: &[T::AccountId], outgoing: &[T::AccountId], new: &[T::AccountId]) {
+		<ActiveSettlers<T>>::put(new.to_vec());
+		Self::deposit_event(RawEvent::SettlersChanged(incoming.to_vec(), outgoing.to_vec()));
+	}
+}
+
 impl<T: Trait> Module<T> {
 	
 	/// Check if we have fetched Weather info before. If yes, we use the cached version that is
 	///   stored in off-chain worker storage `storage`. If no, we fetch the remote info and then
 	///   write the info into the storage for future retrieval.
-	fn fetch_if_needed(tx: (T::AccountId, BalanceOf<T>)) -> Result<(), Error<T>> {
-		debug::info!("Tx: {:#?}, {:#?}", &tx.0, &tx.1);
+	fn fetch_if_needed(tx: PendingBetOf<T>) -> Result<(), Error<T>> {
+		debug::info!("Tx: {:#?}, {:#?}", &tx.better, &tx.wager);
 		// Start off by creating a reference to Local Storage value.
 		// Since the local storage is common for all offchain workers, it's a good practice
 		// to prepend our entry with the pallet name.
@@ -157,20 +271,20 @@ impl<T: Trait> Module<T> {
 				//   `Some(Some(false))`: the lock is free
 				//   `Some(Some(true))`: the lock is held
 
-				// If the lock has never been set or is free (false), return true to execute `fetch_n_parse`
+				// If the lock has never been set or is free (false), return true to execute `fetch_seed`
 				None | Some(Some(false)) => Ok(true),
 
-				// Otherwise, someone already hold the lock (true), we want to skip `fetch_n_parse`.
+				// Otherwise, someone already hold the lock (true), we want to skip `fetch_seed`.
 				// Covering cases: `Some(None)` and `Some(Some(true))`
 				_ => Err(<Error<T>>::AlreadyFetched),
 			}
 		});
 
 		// Cases of `res` returned result:
-		//   `Err(<Error<T>>)` - lock is held, so we want to skip `fetch_n_parse` function.
+		//   `Err(<Error<T>>)` - lock is held, so we want to skip `fetch_seed` function.
 		//   `Ok(Err(true))` - Another ocw is writing to the storage while we set it,
-		//                     we also skip `fetch_n_parse` in this case.
-		//   `Ok(Ok(true))` - successfully acquire the lock, so we run `fetch_n_parse`
+		//                     we also skip `fetch_seed` in this case.
+		//   `Ok(Ok(true))` - successfully acquire the lock, so we run `fetch_seed`
 		if let Ok(Ok(true)) = res {
 			let signer = Signer::<T, T::AuthorityId>::all_accounts();
 			if !signer.can_sign() {
@@ -178,20 +292,11 @@ impl<T: Trait> Module<T> {
 				s_lock.set(&false);
 				return Err(<Error<T>>::SubmitError);
 			}
-			match Self::fetch_n_parse() {
-				Ok(true) => {
-					debug::info!("Fetch and parse returned true.");
+			match Self::fetch_seed(Subject::Bet(tx.id)) {
+				Ok(reveal) => {
+					debug::info!("Fetch and parse returned a seed.");
 					s_lock.set(&false);
-					let _ = signer.send_signed_transaction(|_acct| {
-						Call::submit_signed(tx.0.clone(), tx.1.clone(), true)
-					});
-				},
-				Ok(false) => {
-					debug::info!("Fetch and parse returned false.");
-					s_lock.set(&false);
-					let _ = signer.send_signed_transaction(|_acct| {
-						Call::submit_signed(tx.0.clone(), tx.1.clone(), false)
-					});
+					let _ = signer.send_signed_transaction(|_acct| Call::submit_fair(tx.id, reveal.clone()));
 				},
 				Err(err) => {
 					debug::info!("Fetch and parse returned error.");
@@ -203,39 +308,95 @@ impl<T: Trait> Module<T> {
 		Ok(())
 	}
 
-	/// Fetch from remote and deserialize the JSON to a struct
-	fn fetch_n_parse() -> Result<bool, Error<T>> {
-		let resp_bytes = Self::fetch_from_remote().map_err(|e| {
+	/// Fetch the seed of lottery round `round` and submit it. The tickets are drawn on chain.
+	fn draw(round: RoundIndex) -> Result<(), Error<T>> {
+		let signer = Signer::<T, T::AuthorityId>::all_accounts();
+		if !signer.can_sign() {
+			debug::error!("No local account available");
+			return Err(<Error<T>>::SubmitError);
+		}
+		let reveal = Self::fetch_seed(Subject::Lottery(round))?;
+		let _ = signer.send_signed_transaction(|_acct| Call::submit_draw(round, reveal.clone()));
+		Ok(())
+	}
+
+	/// Fetch the seed of prediction round `round_id` and submit it. The winning outcome is drawn
+	/// on chain.
+	fn resolve(round_id: RoundId) -> Result<(), Error<T>> {
+		let signer = Signer::<T, T::AuthorityId>::all_accounts();
+		if !signer.can_sign() {
+			debug::error!("No local account available");
+			return Err(<Error<T>>::SubmitError);
+		}
+		let reveal = Self::fetch_seed(Subject::Prediction(round_id))?;
+		let _ = signer.send_signed_transaction(|_acct| Call::submit_resolution(round_id, reveal.clone()));
+		Ok(())
+	}
+
+	/// The configured oracle endpoint, or `HTTP_REMOTE_REQUEST_STRING` if none is set.
+	fn oracle_url() -> Vec<u8> {
+		let endpoint = Self::oracle_endpoint();
+		if endpoint.is_empty() {
+			HTTP_REMOTE_REQUEST_STRING.as_bytes().to_vec()
+		} else {
+			endpoint
+		}
+	}
+
+	/// Fetch the oracle's signed seed at the index the chain assigned `subject`.
+	fn fetch_seed(subject: Subject) -> Result<Reveal, Error<T>> {
+		let index = <chance::Module<T>>::seed_index(subject).ok_or(<Error<T>>::ConvertionError)?;
+		let id = match subject {
+			Subject::Bet(id) => id,
+			Subject::Lottery(round) => round.into(),
+			Subject::Prediction(round_id) => round_id.into(),
+		};
+		let mut url = Self::oracle_url();
+		url.push(b'?');
+		url.extend_from_slice(subject.kind().as_bytes());
+		url.push(b'=');
+		append_decimal(&mut url, id);
+		url.extend_from_slice(b"&index=");
+		append_decimal(&mut url, index);
+		let resp_bytes = Self::fetch_from_remote(&url).map_err(|e| {
 			debug::error!("fetch_from_remote error: {:?}", e);
 			<Error<T>>::HttpFetchingError
 		})?;
 
 		let resp_str = str::from_utf8(&resp_bytes).map_err(|_| <Error<T>>::HttpFetchingError)?;
 		debug::info!("Fetch and parse fetched: {:#?}.", resp_str);
-		if resp_str == "1" {
-			return Ok(true);
+		let answer: OracleSeed = serde_json::from_str(resp_str).map_err(|_| <Error<T>>::ConvertionError)?;
+		if answer.index != index {
+			return Err(<Error<T>>::ConvertionError);
 		}
+		let seed = decode_hex(answer.seed).ok_or(<Error<T>>::ConvertionError)?;
+		let signature = decode_hex(answer.signature).ok_or(<Error<T>>::ConvertionError)?;
+		Ok(Reveal { index, seed, signature })
+	}
 
-		// let s = str::replace(&resp_str.replace("]", ""), "[", "");
-		//debug::info!("Fetched String: {:#?}", &s);
-
-		// Deserializing JSON to struct, thanks to `serde` and `serde_derive`
-		// let denied_account: DeniedAccount =
-		// 			serde_json::from_str::<DeniedAccount>(&s).map_err(|_| <Error<T>>::HttpFetchingError)?;
-		// Print out our fetched JSON string
-		// debug::info!("{:#?}", denied_account);
-
-		Ok(false)
+	/// Fetch `remote_url_bytes`, recording the request in the `FETCH_STATS_KEY` statistics.
+	fn fetch_from_remote(remote_url_bytes: &[u8]) -> Result<Vec<u8>, Error<T>> {
+		let started = sp_io::offchain::timestamp();
+		let result = Self::request(remote_url_bytes);
+		let latency = sp_io::offchain::timestamp().diff(&started).millis();
+		let stats = StorageValueRef::persistent(FETCH_STATS_KEY);
+		let _ = stats.mutate(|stats: Option<Option<FetchStats>>| -> Result<FetchStats, ()> {
+			let mut stats = stats.flatten().unwrap_or_default();
+			stats.fetches = stats.fetches.saturating_add(1);
+			if result.is_err() {
+				stats.errors = stats.errors.saturating_add(1);
+			}
+			stats.last_latency_ms = latency;
+			Ok(stats)
+		});
+		result
 	}
 
 	/// This function uses the `offchain::http` API to query the remote Weather information,
 	///   and returns the JSON response as vector of bytes.
-	fn fetch_from_remote() -> Result<Vec<u8>, Error<T>> {
-		let url_with_address = HTTP_REMOTE_REQUEST_STRING;
-		//debug::info!("URL: {}", url_with_address);
-		let remote_url_bytes = url_with_address.as_bytes().to_vec();
+	fn request(remote_url_bytes: &[u8]) -> Result<Vec<u8>, Error<T>> {
 		let remote_url =
-			str::from_utf8(&remote_url_bytes).map_err(|_| <Error<T>>::HttpFetchingError)?;
+			str::from_utf8(remote_url_bytes).map_err(|_| <Error<T>>::HttpFetchingError)?;
 
 		debug::info!("sending request to: {}", remote_url);
 
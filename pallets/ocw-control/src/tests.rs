@@ -1,11 +1,14 @@
 use crate::*;
-use frame_support::{assert_ok, assert_noop, impl_outer_event, impl_outer_origin, parameter_types, weights::Weight};
+use frame_support::{assert_ok, assert_noop, impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types, traits::{OffchainWorker, OnInitialize}, weights::Weight};
 use codec::{alloc::sync::Arc, Decode};
 use parking_lot::RwLock;
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
 use sp_core::{
 	offchain::{
 		testing::{self, OffchainState, PoolState},
-		OffchainExt, TransactionPoolExt,
+		Externalities as OffchainExternalities, HttpError, HttpRequestId, HttpRequestStatus, OffchainExt,
+		OpaqueNetworkState, OpaquePeerId, StorageKind, Timestamp, TransactionPoolExt,
 	},
 	sr25519::{self, Signature},
 	testing::KeyStore,
@@ -14,6 +17,7 @@ use sp_core::{
 };
 use sp_core::{Pair, Public};
 use sp_io::TestExternalities;
+use orml_traits::MultiCurrency;
 use sp_runtime::{
 	testing::{Header, TestXt},
 	traits::{BlakeTwo256, IdentityLookup, Verify, IdentifyAccount},
@@ -27,6 +31,14 @@ impl_outer_origin! {
 	pub enum Origin for Test {}
 }
 
+// Named apart from the pallet's own `Call`, which the offchain worker submits.
+impl_outer_dispatch! {
+	pub enum OuterCall for Test where origin: Origin {
+		system::System,
+		admin::Admin,
+	}
+}
+
 // Configure a mock runtime to test the pallet.
 
 #[derive(Clone, Eq, PartialEq)]
@@ -38,14 +50,16 @@ parameter_types! {
 	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
 }
 
+pub type Tokens = orml_tokens::Module<Test>;
 pub type Balances = pallet_balances::Module<Test>;
 pub type System = frame_system::Module<Test>;
+pub type Scheduler = pallet_scheduler::Module<Test>;
 
 impl system::Trait for Test {
 	type BaseCallFilter = ();
 	type Origin = Origin;
 	type Index = u64;
-	type Call = ();
+	type Call = OuterCall;
 	type BlockNumber = u64;
 	type Hash = H256;
 	type Hashing = BlakeTwo256;
@@ -73,7 +87,24 @@ impl pooler::Trait for Test {
     type Event = ();
     type Balance = u128;
 	type AssetId = u128;
-	type Currency = Balances;
+	type Currency = Tokens;
+	type LegacyCurrencyId = LegacyCurrencyId;
+}
+
+pub type CurrencyId = u32;
+pub const DOT: CurrencyId = 1;
+
+parameter_types! {
+	pub const LegacyCurrencyId: CurrencyId = DOT;
+}
+
+impl orml_tokens::Trait for Test {
+    type Event = ();
+    type Balance = u128;
+    type Amount = i128;
+    type CurrencyId = CurrencyId;
+    type OnReceived = ();
+    type WeightInfo = ();
 }
 
 parameter_types! {
@@ -90,24 +121,127 @@ impl pallet_balances::Trait for Test {
     type MaxLocks = ();
 }
 
+parameter_types! {
+	pub const MaximumSchedulerWeight: Weight = 800_000;
+	pub const MaxScheduledPerBlock: u32 = 10;
+}
+
+impl pallet_scheduler::Trait for Test {
+	type Event = ();
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = OuterCall;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<<Test as system::Trait>::AccountId>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub const MaxSettlers: u32  = 10; 
+	pub const MaxRoleMembers: u32 = 5;
+	pub const RemovalDelay: u64 = 5;
+	pub const SettlerBond: u64 = 100;
+	pub const EraLength: u64 = 20;
+	pub const MaxMissedSettlements: u32 = 1;
+	pub const MissedSettlementSlash: Perbill = Perbill::from_percent(10);
+	pub const MaxAverageLatency: u64 = 10;
+	pub const MaxDisputes: u32 = 2;
+	pub const ParameterChangeDelay: u64 = 3;
 }
 
 
 impl admin::Trait for Test {
 	type Event = ();
 	type MaxSettlers = MaxSettlers;
+	type MaxRoleMembers = MaxRoleMembers;
+	type AdminOrigin = frame_system::EnsureRoot<<Test as system::Trait>::AccountId>;
+	type RemovalDelay = RemovalDelay;
+	type SettlersChanged = OcwDemo;
+	type Currency = Balances;
+	type SettlerBond = SettlerBond;
+	type Slash = ();
+	type EraLength = EraLength;
+	type MaxMissedSettlements = MaxMissedSettlements;
+	type MissedSettlementSlash = MissedSettlementSlash;
+	type MaxAverageLatency = MaxAverageLatency;
+	type MaxDisputes = MaxDisputes;
+	type Proposal = OuterCall;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type ParameterChangeDelay = ParameterChangeDelay;
 }
 
 parameter_types! {
 	pub const SystemDecimals: u128  = 100000000000;
+	pub TreasuryAccount: <Test as system::Trait>::AccountId = get_account_id_from_seed::<sr25519::Public>("Treasury");
+	pub const TreasuryCut: Perbill = Perbill::from_percent(10);
+	pub const SettlerCut: Perbill = Perbill::from_percent(10);
+	pub const CircuitBreakerWindow: u64 = 10;
+	pub const CircuitBreakerThreshold: Perbill = Perbill::from_percent(50);
+	pub const SettlementDeadline: u64 = 5;
+	pub const CancellationWindow: u64 = 2;
+	pub const CancellationFee: Perbill = Perbill::from_percent(10);
+	pub const MaxBetHistory: u32 = 3;
+	pub const BetHistoryRetention: u64 = 100;
+	pub const BlocksPerDay: u64 = 10;
+	pub const LimitIncreaseCooldown: u64 = 5;
+	pub const ReferralCut: Perbill = Perbill::from_percent(10);
+	pub const MaxReferralDepth: u32 = 3;
 }
 
 impl chance::Trait for Test {
     type Event = ();
-	type Currency = Balances;
+	type Currency = Tokens;
 	type SystemDecimals = SystemDecimals;
+	type TreasuryAccount = TreasuryAccount;
+	type TreasuryCut = TreasuryCut;
+	type SettlerCut = SettlerCut;
+	type CircuitBreakerWindow = CircuitBreakerWindow;
+	type CircuitBreakerThreshold = CircuitBreakerThreshold;
+	type SettlementDeadline = SettlementDeadline;
+	type CancellationWindow = CancellationWindow;
+	type CancellationFee = CancellationFee;
+	type MaxBetHistory = MaxBetHistory;
+	type BetHistoryRetention = BetHistoryRetention;
+	type BlocksPerDay = BlocksPerDay;
+	type LimitIncreaseCooldown = LimitIncreaseCooldown;
+	type ReferralCut = ReferralCut;
+	type MaxReferralDepth = MaxReferralDepth;
+	type LegacyCurrencyId = LegacyCurrencyId;
+}
+
+parameter_types! {
+	pub const RoundLength: u64 = 10;
+	pub const LotteryCurrency: CurrencyId = DOT;
+	pub const TicketPrice: u128 = 1000;
+	pub const JackpotCut: Perbill = Perbill::from_percent(80);
+	pub PrizeTiers: Vec<Perbill> = vec![Perbill::from_percent(50), Perbill::from_percent(20)];
+	pub const MaxTicketsPerRound: u32 = 5;
+}
+
+impl lottery::Trait for Test {
+	type Event = ();
+	type RoundLength = RoundLength;
+	type LotteryCurrency = LotteryCurrency;
+	type TicketPrice = TicketPrice;
+	type JackpotCut = JackpotCut;
+	type PrizeTiers = PrizeTiers;
+	type MaxTicketsPerRound = MaxTicketsPerRound;
+}
+
+parameter_types! {
+	pub const ProtocolFee: Perbill = Perbill::from_percent(10);
+	pub const MaxOutcomes: u32 = 4;
+	pub const MaxStakersPerRound: u32 = 3;
+}
+
+impl prediction::Trait for Test {
+	type Event = ();
+	type CreateOrigin = frame_system::EnsureRoot<<Test as system::Trait>::AccountId>;
+	type ProtocolFee = ProtocolFee;
+	type MaxOutcomes = MaxOutcomes;
+	type MaxStakersPerRound = MaxStakersPerRound;
 }
 
 type TestExtrinsic = TestXt<Call<Test>, ()>;
@@ -156,6 +290,9 @@ where
 pub type OcwDemo = Module<Test>;
 pub type Chance = chance::Module<Test>;
 pub type Pooler = pooler::Module<Test>;
+pub type Admin = admin::Module<Test>;
+pub type Lottery = lottery::Module<Test>;
+pub type Prediction = prediction::Module<Test>;
 
 
 struct ExternalityBuilder;
@@ -166,37 +303,297 @@ impl ExternalityBuilder {
 		Arc<RwLock<PoolState>>,
 		Arc<RwLock<OffchainState>>,
 	) {
+		let (offchain, offchain_state) = testing::TestOffchainExt::new();
+		let (t, pool_state) = Self::build_with(OffchainExt::new(offchain), true);
+		(t, pool_state, offchain_state)
+	}
+
+	/// Build the externalities around `offchain`, with an `ocwc` key in the keystore if
+	/// `with_key` is set.
+	fn build_with(offchain: OffchainExt, with_key: bool) -> (TestExternalities, Arc<RwLock<PoolState>>) {
 		const PHRASE: &str =
 			"expire stage crawl shell boss any story swamp skull yellow bamboo copy";
 
-		let (offchain, offchain_state) = testing::TestOffchainExt::new();
 		let (pool, pool_state) = testing::TestTransactionPoolExt::new();
 		let keystore = KeyStore::new();
-		keystore
-			.write()
-			.sr25519_generate_new(KEY_TYPE, Some(&format!("{}/hunter1", PHRASE)))
-            .unwrap();
+		if with_key {
+			keystore
+				.write()
+				.sr25519_generate_new(KEY_TYPE, Some(&format!("{}/hunter1", PHRASE)))
+				.unwrap();
+		}
             
         let acct: <Test as system::Trait>::AccountId = Default::default();
 
 		let mut storage = system::GenesisConfig::default()
 			.build_storage::<Test>()
 			.unwrap();
+        orml_tokens::GenesisConfig::<Test> {
+                endowed_accounts: vec![(acct, DOT, 100000000000000000)],
+			}.assimilate_storage(&mut storage).unwrap();
         pallet_balances::GenesisConfig::<Test> {
-                balances: vec![(acct, 100000000000000000)],
+                balances: vec![(acct, 1000), (get_account_id_from_seed::<sr25519::Public>("Alice"), 1000)],
 			}.assimilate_storage(&mut storage).unwrap();
 		admin::GenesisConfig::<Test> {
 				settlers: vec![acct],
+				..Default::default()
+		}.assimilate_storage(&mut storage).unwrap();
+		chance::GenesisConfig::<Test> {
+				oracle_key: Some(oracle().public()),
+				..Default::default()
 		}.assimilate_storage(&mut storage).unwrap();
 		let mut t = TestExternalities::from(storage);
-		t.register_extension(OffchainExt::new(offchain));
+		t.register_extension(offchain);
 		t.register_extension(TransactionPoolExt::new(pool));
 		t.register_extension(KeystoreExt(keystore));
 		t.execute_with(|| System::set_block_number(1));
-		(t, pool_state, offchain_state)
+		(t, pool_state)
+	}
+}
+
+/// How the scripted oracle answers one request.
+#[derive(Clone, Debug)]
+enum Reply {
+	/// Answer with a status code and a body.
+	Status(u16, Vec<u8>),
+	/// Never answer before the deadline.
+	Timeout,
+}
+
+#[derive(Default)]
+struct Script {
+	/// The requests still expected, in order, with their replies.
+	expected: VecDeque<(String, Reply)>,
+	/// The replies of started requests and how much of their body was read, by request id.
+	started: Vec<(Reply, usize)>,
+}
+
+/// Offchain externalities whose HTTP requests must match a script. Every request has to be the
+/// next scripted one and gets its scripted reply; anything else is served by `TestOffchainExt`.
+struct ScriptedOffchainExt {
+	inner: testing::TestOffchainExt,
+	script: Arc<RwLock<Script>>,
+}
+
+impl ScriptedOffchainExt {
+	fn reply(&self, id: HttpRequestId) -> Option<(Reply, usize)> {
+		self.script.read().started.get(id.0 as usize).cloned()
+	}
+}
+
+impl OffchainExternalities for ScriptedOffchainExt {
+	fn is_validator(&self) -> bool {
+		self.inner.is_validator()
+	}
+
+	fn network_state(&self) -> Result<OpaqueNetworkState, ()> {
+		self.inner.network_state()
+	}
+
+	fn timestamp(&mut self) -> Timestamp {
+		self.inner.timestamp()
+	}
+
+	fn sleep_until(&mut self, deadline: Timestamp) {
+		self.inner.sleep_until(deadline)
+	}
+
+	fn random_seed(&mut self) -> [u8; 32] {
+		self.inner.random_seed()
+	}
+
+	fn local_storage_set(&mut self, kind: StorageKind, key: &[u8], value: &[u8]) {
+		self.inner.local_storage_set(kind, key, value)
+	}
+
+	fn local_storage_clear(&mut self, kind: StorageKind, key: &[u8]) {
+		self.inner.local_storage_clear(kind, key)
+	}
+
+	fn local_storage_compare_and_set(
+		&mut self,
+		kind: StorageKind,
+		key: &[u8],
+		old_value: Option<&[u8]>,
+		new_value: &[u8],
+	) -> bool {
+		self.inner.local_storage_compare_and_set(kind, key, old_value, new_value)
+	}
+
+	fn local_storage_get(&mut self, kind: StorageKind, key: &[u8]) -> Option<Vec<u8>> {
+		self.inner.local_storage_get(kind, key)
+	}
+
+	fn http_request_start(&mut self, method: &str, uri: &str, _meta: &[u8]) -> Result<HttpRequestId, ()> {
+		let mut script = self.script.write();
+		let (expected, reply) = script.expected.pop_front()
+			.unwrap_or_else(|| panic!("Unexpected request: {} {}", method, uri));
+		assert_eq!((method, uri), ("GET", expected.as_str()));
+		script.started.push((reply, 0));
+		Ok(HttpRequestId(script.started.len() as u16 - 1))
+	}
+
+	fn http_request_add_header(&mut self, request_id: HttpRequestId, _name: &str, _value: &str) -> Result<(), ()> {
+		self.reply(request_id).map(|_| ()).ok_or(())
+	}
+
+	fn http_request_write_body(
+		&mut self,
+		request_id: HttpRequestId,
+		_chunk: &[u8],
+		_deadline: Option<Timestamp>,
+	) -> Result<(), HttpError> {
+		self.reply(request_id).map(|_| ()).ok_or(HttpError::Invalid)
+	}
+
+	fn http_response_wait(&mut self, ids: &[HttpRequestId], _deadline: Option<Timestamp>) -> Vec<HttpRequestStatus> {
+		ids.iter().map(|id| match self.reply(*id) {
+			Some((Reply::Status(code, _), _)) => HttpRequestStatus::Finished(code),
+			Some((Reply::Timeout, _)) => HttpRequestStatus::DeadlineReached,
+			None => HttpRequestStatus::Invalid,
+		}).collect()
+	}
+
+	fn http_response_headers(&mut self, _request_id: HttpRequestId) -> Vec<(Vec<u8>, Vec<u8>)> {
+		Vec::new()
+	}
+
+	fn http_response_read_body(
+		&mut self,
+		request_id: HttpRequestId,
+		buffer: &mut [u8],
+		_deadline: Option<Timestamp>,
+	) -> Result<usize, HttpError> {
+		let mut script = self.script.write();
+		match script.started.get_mut(request_id.0 as usize) {
+			Some((Reply::Status(_, body), read)) => {
+				let len = buffer.len().min(body.len() - *read);
+				buffer[..len].copy_from_slice(&body[*read..*read + len]);
+				*read += len;
+				Ok(len)
+			},
+			Some((Reply::Timeout, _)) => Err(HttpError::DeadlineReached),
+			None => Err(HttpError::Invalid),
+		}
+	}
+
+	fn set_authorized_nodes(&mut self, nodes: Vec<OpaquePeerId>, authorized_only: bool) {
+		self.inner.set_authorized_nodes(nodes, authorized_only)
+	}
+}
+
+/// Drives the offchain worker against a scripted oracle and collects what it submits.
+struct OracleHarness {
+	ext: TestExternalities,
+	pool: Arc<RwLock<PoolState>>,
+	script: Arc<RwLock<Script>>,
+}
+
+impl OracleHarness {
+	fn new() -> Self {
+		Self::build(true)
+	}
+
+	/// A harness whose keystore has no `ocwc` key, so nothing can be submitted.
+	fn without_keys() -> Self {
+		Self::build(false)
+	}
+
+	fn build(with_key: bool) -> Self {
+		let (inner, _) = testing::TestOffchainExt::new();
+		let script = Arc::new(RwLock::new(Script::default()));
+		let offchain = OffchainExt::new(ScriptedOffchainExt { inner, script: script.clone() });
+		let (ext, pool) = ExternalityBuilder::build_with(offchain, with_key);
+		OracleHarness { ext, pool, script }
+	}
+
+	/// Expect a request for `query` on the oracle and answer it with `code` and `body`.
+	fn reply(&mut self, query: &str, code: u16, body: &str) -> &mut Self {
+		self.expect(query, Reply::Status(code, body.as_bytes().to_vec()))
+	}
+
+	/// Expect a request for `query` on the oracle and let it time out.
+	fn time_out(&mut self, query: &str) -> &mut Self {
+		self.expect(query, Reply::Timeout)
+	}
+
+	fn expect(&mut self, query: &str, reply: Reply) -> &mut Self {
+		let uri = format!("{}{}", HTTP_REMOTE_REQUEST_STRING, query);
+		self.script.write().expected.push_back((uri, reply));
+		self
+	}
+
+	fn execute_with<R>(&mut self, execute: impl FnOnce() -> R) -> R {
+		self.ext.execute_with(execute)
+	}
+
+	/// Run the offchain worker at the end of each of `blocks`.
+	fn run_blocks(&mut self, blocks: RangeInclusive<u64>) {
+		self.execute_with(|| {
+			for block in blocks {
+				System::set_block_number(block);
+				OcwDemo::offchain_worker(block);
+			}
+		})
+	}
+
+	/// The calls the worker submitted since the last time, checking they are signed.
+	fn take_calls(&mut self) -> Vec<Call<Test>> {
+		self.pool.write().transactions.drain(..).map(|tx| {
+			let tx = TestExtrinsic::decode(&mut &tx[..]).unwrap();
+			assert!(tx.signature.is_some());
+			tx.call
+		}).collect()
+	}
+
+	/// Check every scripted request was made.
+	fn assert_done(&self) {
+		let script = self.script.read();
+		assert!(script.expected.is_empty(), "Requests never made: {:?}", script.expected);
 	}
 }
 
+/// A bet of the default account in the `DOT` pool, as placed at block 1.
+fn place_bet() {
+	let acct: <Test as system::Trait>::AccountId = Default::default();
+	assert_ok!(Pooler::deposit(Origin::signed(acct), DOT, 100000000000000));
+	assert_ok!(Chance::bet(Origin::signed(acct), DOT, 1000000000000));
+}
+
+/// The key the test oracle signs its seeds with.
+fn oracle() -> sr25519::Pair {
+	sr25519::Pair::from_seed(&[9; 32])
+}
+
+/// The test oracle's reveal of `seed` at `index` for `subject`.
+fn signed(subject: Subject, index: u64, seed: &[u8]) -> Reveal {
+	let signature = oracle().sign(&pallet_chance_fairness::signed_message(subject, index, seed));
+	Reveal { index, seed: seed.to_vec(), signature: signature.0.to_vec() }
+}
+
+/// The block the outcomes of bets placed in block 1 depend on, two blocks of cancellation later.
+const OUTCOME_BLOCK: u64 = 3;
+
+/// Build the block the outcomes of bets placed in block 1 depend on and move past it.
+fn close_cancellation_windows() {
+	system::BlockHash::<Test>::insert(OUTCOME_BLOCK, H256::repeat_byte(3));
+	System::set_block_number(OUTCOME_BLOCK + 1);
+}
+
+/// A seed settling bet `bet_id`, placed in block 1, with the given outcome.
+fn seed_for(bet_id: BetId, won: bool) -> Vec<u8> {
+	let block_hash = System::block_hash(OUTCOME_BLOCK);
+	(0u8..).map(|i| vec![i; 32])
+		.find(|seed| pallet_chance_fairness::is_win(seed, block_hash.as_ref(), bet_id) == won)
+		.unwrap()
+}
+
+/// An oracle reply carrying `reveal`.
+fn seed_reply(reveal: &Reveal) -> String {
+	let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+	format!(r#"{{"seed":"{}","index":{},"signature":"{}"}}"#, hex(&reveal.seed), reveal.index, hex(&reveal.signature))
+}
+
 
 #[test]
 fn test_ocw_call_bet_won() {
@@ -204,17 +601,20 @@ fn test_ocw_call_bet_won() {
 	t.execute_with(|| {
         let acct: <Test as system::Trait>::AccountId = Default::default();
 
-		assert_ok!(Pooler::deposit(Origin::signed(acct), 100000000000000));
-		assert_eq!(Pooler::balance(acct), 100000000000000);
-		assert_ok!(Chance::bet(Origin::signed(acct), 1000000000000));
+		assert_ok!(Pooler::deposit(Origin::signed(acct), DOT, 100000000000000));
+		assert_eq!(Pooler::balance(DOT, acct), 100000000000000);
+		assert_ok!(Chance::bet(Origin::signed(acct), DOT, 1000000000000));
 		println!("check on bets before {:#?}", Chance::scheduled_bet());
-		let bet = [(acct,990000000000,),];
+		let bet = [chance::PendingBet { id: 0, better: acct, currency_id: DOT, wager: 990000000000, settler_fee: 1000000000, placed_at: 1 }];
 		assert_eq!(Chance::scheduled_bet(), bet);
-		assert_ok!(OcwDemo::submit_signed(Origin::signed(acct), acct, 990000000000, true));
+		close_cancellation_windows();
+		let reveal = signed(Subject::Bet(0), 0, &seed_for(0, true));
+		assert_ok!(OcwDemo::submit_fair(Origin::signed(acct), 0, reveal));
 		let bet_after = [];
 		assert_eq!(Chance::scheduled_bet(), bet_after);
 		println!("check on bets after {:#?}", Chance::scheduled_bet());
-		assert_eq!(Balances::free_balance(Chance::account_id()), 99020000000000);
+		assert_eq!(Tokens::free_balance(DOT, &Chance::account_id()), 99018000000000);
+		assert_eq!(Tokens::free_balance(DOT, &TreasuryAccount::get()), 1000000000);
 
 
 	})
@@ -226,20 +626,46 @@ fn test_ocw_call_bet_lost() {
 	t.execute_with(|| {
         let acct: <Test as system::Trait>::AccountId = Default::default();
 
-		assert_ok!(Pooler::deposit(Origin::signed(acct), 100000000000000));
-		assert_eq!(Pooler::balance(acct), 100000000000000);
-		assert_ok!(Chance::bet(Origin::signed(acct), 1000000000000));
+		assert_ok!(Pooler::deposit(Origin::signed(acct), DOT, 100000000000000));
+		assert_eq!(Pooler::balance(DOT, acct), 100000000000000);
+		assert_ok!(Chance::bet(Origin::signed(acct), DOT, 1000000000000));
 		println!("check on bets before {:#?}", Chance::scheduled_bet());
-		let bet = [(acct,990000000000,),];
+		let bet = [chance::PendingBet { id: 0, better: acct, currency_id: DOT, wager: 990000000000, settler_fee: 1000000000, placed_at: 1 }];
 		assert_eq!(Chance::scheduled_bet(), bet);
-		assert_ok!(OcwDemo::submit_signed(Origin::signed(acct), acct, 990000000000, false));
+		close_cancellation_windows();
+		let reveal = signed(Subject::Bet(0), 0, &seed_for(0, false));
+		assert_ok!(OcwDemo::submit_fair(Origin::signed(acct), 0, reveal));
 		let bet_after = [];
 		assert_eq!(Chance::scheduled_bet(), bet_after);
 		println!("check on bets after {:#?}", Chance::scheduled_bet());
 		
-		println!("account {:#?}", Balances::free_balance(Chance::account_id()));
-		assert_eq!(Balances::free_balance(Chance::account_id()), 101000000000000);
+		println!("account {:#?}", Tokens::free_balance(DOT, &Chance::account_id()));
+		assert_eq!(Tokens::free_balance(DOT, &Chance::account_id()), 100998000000000);
+
+	})
+}
+
+#[test]
+fn test_ocw_settles_bet_with_oracle_seed() {
+  let (mut t, _, _) = ExternalityBuilder::build();
+	t.execute_with(|| {
+        let acct: <Test as system::Trait>::AccountId = Default::default();
 
+		assert_ok!(Pooler::deposit(Origin::signed(acct), DOT, 100000000000000));
+		assert_ok!(Chance::bet(Origin::signed(acct), DOT, 1000000000000));
+		let reveal = signed(Subject::Bet(0), 0, &[7; 32]);
+		assert_noop!(
+			OcwDemo::submit_fair(Origin::signed(acct), 0, reveal.clone()),
+			chance::Error::<Test>::OutcomeNotReady
+		);
+		close_cancellation_windows();
+		assert_noop!(
+			OcwDemo::submit_fair(Origin::signed(acct), 0, Reveal { signature: vec![], ..reveal.clone() }),
+			chance::Error::<Test>::InvalidOracleSignature
+		);
+		assert_ok!(OcwDemo::submit_fair(Origin::signed(acct), 0, reveal));
+		assert!(Chance::scheduled_bet().is_empty());
+		assert!(Chance::verify_bet(0).is_some());
 	})
 }
 
@@ -250,16 +676,313 @@ fn test_ocw_called_by_non_settler_should_fail() {
         let acct: <Test as system::Trait>::AccountId = Default::default();
 		let non_settler = get_account_id_from_seed::<sr25519::Public>("Alice");
 
-		assert_ok!(Pooler::deposit(Origin::signed(acct), 100000000000000));
-		assert_eq!(Pooler::balance(acct), 100000000000000);
-		assert_ok!(Chance::bet(Origin::signed(acct), 1000000000000));
+		assert_ok!(Pooler::deposit(Origin::signed(acct), DOT, 100000000000000));
+		assert_eq!(Pooler::balance(DOT, acct), 100000000000000);
+		assert_ok!(Chance::bet(Origin::signed(acct), DOT, 1000000000000));
 		println!("check on bets before {:#?}", Chance::scheduled_bet());
-		let bet = [(acct,990000000000,),];
+		let bet = [chance::PendingBet { id: 0, better: acct, currency_id: DOT, wager: 990000000000, settler_fee: 1000000000, placed_at: 1 }];
 		assert_eq!(Chance::scheduled_bet(), bet);
-		assert_noop!(OcwDemo::submit_signed(Origin::signed(non_settler), acct, 990000000000, false), admin::Error::<Test>::NotSettler);
+		let reveal = signed(Subject::Bet(0), 0, &seed_for(0, false));
+		assert_noop!(OcwDemo::submit_fair(Origin::signed(non_settler), 0, reveal), admin::Error::<Test>::NotSettler);
+	})
+}
+
+#[test]
+fn test_settler_changes_are_reported_to_ocw() {
+  let (mut t, _, _) = ExternalityBuilder::build();
+	t.execute_with(|| {
+        let acct: <Test as system::Trait>::AccountId = Default::default();
+		let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+		assert_eq!(OcwDemo::active_settlers(), vec![acct]);
+
+		assert_ok!(Admin::add_setter(Origin::root(), alice));
+		let mut expected = vec![acct, alice];
+		expected.sort();
+		assert_eq!(OcwDemo::active_settlers(), expected);
+
+		assert_ok!(Admin::schedule_settler_removal(Origin::root(), alice));
+		Admin::on_initialize(5);
+		assert_eq!(OcwDemo::active_settlers().len(), 2);
+		Admin::on_initialize(6);
+		assert_eq!(OcwDemo::active_settlers(), vec![acct]);
+	})
+}
+
+#[test]
+fn test_oracle_endpoint_is_set_by_oracle_manager() {
+  let (mut t, _, _) = ExternalityBuilder::build();
+	t.execute_with(|| {
+		let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+		let endpoint = b"http://oracle:3000/random".to_vec();
+		assert!(OcwDemo::oracle_endpoint().is_empty());
+
+		assert_noop!(
+			OcwDemo::set_oracle_endpoint(Origin::signed(alice), endpoint.clone()),
+			admin::Error::<Test>::MissingRole
+		);
+		assert_ok!(Admin::grant_role(Origin::root(), admin::Role::OracleManager, alice));
+		assert_ok!(OcwDemo::set_oracle_endpoint(Origin::signed(alice), endpoint.clone()));
+		assert_eq!(OcwDemo::oracle_endpoint(), endpoint);
 	})
 }
 
+#[test]
+fn test_ocw_submits_lottery_draw() {
+  let (mut t, _, _) = ExternalityBuilder::build();
+	t.execute_with(|| {
+        let acct: <Test as system::Trait>::AccountId = Default::default();
+		let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+
+		assert_ok!(Lottery::buy_tickets(Origin::signed(acct), 2));
+		Lottery::on_initialize(10);
+		assert_eq!(Lottery::pending_draws(), vec![0]);
+		System::set_block_number(11);
+
+		let reveal = signed(Subject::Lottery(0), 0, &[5; 32]);
+		assert_noop!(OcwDemo::submit_draw(Origin::signed(alice), 0, reveal.clone()), admin::Error::<Test>::NotSettler);
+		assert_ok!(OcwDemo::submit_draw(Origin::signed(acct), 0, reveal));
+		assert!(Lottery::pending_draws().is_empty());
+		assert_eq!(Lottery::winners(0), vec![(acct, 800), (acct, 320)]);
+	})
+}
+
+#[test]
+fn test_ocw_submits_prediction_resolution() {
+  let (mut t, _, _) = ExternalityBuilder::build();
+	t.execute_with(|| {
+        let acct: <Test as system::Trait>::AccountId = Default::default();
+		let alice = get_account_id_from_seed::<sr25519::Public>("Alice");
+
+		assert_ok!(Prediction::create_round(Origin::root(), DOT, 2, 5));
+		assert_ok!(Prediction::stake(Origin::signed(acct), 0, 1, 1000));
+		Prediction::on_initialize(6);
+		assert_eq!(Prediction::pending_resolutions(), vec![0]);
+		System::set_block_number(7);
+
+		let reveal = signed(Subject::Prediction(0), 0, &[5; 32]);
+		assert_noop!(OcwDemo::submit_resolution(Origin::signed(alice), 0, reveal.clone()), admin::Error::<Test>::NotSettler);
+		assert_ok!(OcwDemo::submit_resolution(Origin::signed(acct), 0, reveal));
+		assert_eq!(Prediction::result(0), Some(Prediction::winning_outcome(0, 2, &[5; 32], System::block_hash(6).as_ref())));
+		assert!(Prediction::pending_resolutions().is_empty());
+	})
+}
+
+#[test]
+fn test_oracle_requests_are_counted_for_metrics() {
+	let (mut t, _, offchain_state) = ExternalityBuilder::build();
+	let reveal = signed(Subject::Bet(0), 0, &[7; 32]);
+	offchain_state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: "http://localhost:3000/random?bet=0&index=0".into(),
+		response: Some(seed_reply(&reveal).into_bytes()),
+		sent: true,
+		..Default::default()
+	});
+	t.execute_with(|| {
+		place_bet();
+		let stats = StorageValueRef::persistent(FETCH_STATS_KEY);
+		assert_eq!(OcwDemo::fetch_seed(Subject::Bet(0)).ok(), Some(reveal));
+		assert_eq!(stats.get::<FetchStats>(), Some(Some(FetchStats { fetches: 1, errors: 0, last_latency_ms: 0 })));
+
+		assert!(OcwDemo::fetch_from_remote(&[0xff]).is_err());
+		assert_eq!(stats.get::<FetchStats>(), Some(Some(FetchStats { fetches: 2, errors: 1, last_latency_ms: 0 })));
+	})
+}
+
+#[test]
+fn test_harness_settles_bet_once_it_cannot_be_cancelled() {
+	let mut harness = OracleHarness::new();
+	harness.execute_with(place_bet);
+	let reveal = signed(Subject::Bet(0), 0, &[7; 32]);
+	harness.reply("?bet=0&index=0", 200, &seed_reply(&reveal));
+
+	// the bet can be cancelled until block 3, so the oracle is only asked then
+	harness.run_blocks(1..=2);
+	assert!(harness.take_calls().is_empty());
+	harness.run_blocks(3..=3);
+	assert_eq!(harness.take_calls(), vec![Call::submit_fair(0, reveal)]);
+	harness.assert_done();
+}
+
+#[test]
+fn test_harness_http_failures_are_retried_next_block() {
+	let mut harness = OracleHarness::new();
+	harness.execute_with(place_bet);
+	harness
+		.reply("?bet=0&index=0", 500, "")
+		.time_out("?bet=0&index=0")
+		.reply("?bet=0&index=0", 404, "not found")
+		.reply("?bet=0&index=0", 200, &seed_reply(&signed(Subject::Bet(0), 0, &[1; 32])));
+
+	harness.run_blocks(3..=5);
+	assert!(harness.take_calls().is_empty());
+	harness.run_blocks(6..=6);
+	assert_eq!(harness.take_calls().len(), 1);
+	harness.assert_done();
+}
+
+#[test]
+fn test_harness_http_fetching_error() {
+	let mut harness = OracleHarness::new();
+	harness.execute_with(place_bet);
+	harness.reply("?bet=0&index=0", 503, "").time_out("?bet=0&index=0");
+	harness.execute_with(|| {
+		System::set_block_number(3);
+		let bet = Chance::scheduled_bet()[0].clone();
+		assert!(matches!(OcwDemo::fetch_if_needed(bet.clone()), Err(Error::<Test>::HttpFetchingError)));
+		assert!(matches!(OcwDemo::fetch_if_needed(bet), Err(Error::<Test>::HttpFetchingError)));
+		// the lock is released after a failure
+		assert_eq!(StorageValueRef::persistent(b"ocw-postgres::lock").get::<bool>(), Some(Some(false)));
+	});
+	assert!(harness.take_calls().is_empty());
+	harness.assert_done();
+}
+
+#[test]
+fn test_harness_convertion_error() {
+	let mut harness = OracleHarness::new();
+	harness.execute_with(place_bet);
+	harness
+		.reply("?bet=0&index=0", 200, "garbage")
+		.reply("?bet=0&index=0", 200, r#"{"seed":"zz","index":0,"signature":"00"}"#)
+		.reply("?bet=0&index=0", 200, r#"{"seed":"07"}"#)
+		.reply("?bet=0&index=0", 200, r#"{"seed":"07","index":1,"signature":"00"}"#);
+	harness.execute_with(|| {
+		System::set_block_number(3);
+		let bet = Chance::scheduled_bet()[0].clone();
+		for _ in 0..4 {
+			assert!(matches!(OcwDemo::fetch_if_needed(bet.clone()), Err(Error::<Test>::ConvertionError)));
+		}
+	});
+	assert!(harness.take_calls().is_empty());
+	harness.assert_done();
+}
+
+#[test]
+fn test_harness_answers_for_other_indices_are_convertion_errors() {
+	let mut harness = OracleHarness::new();
+	let acct: <Test as system::Trait>::AccountId = Default::default();
+	harness.execute_with(|| {
+		assert_ok!(Lottery::buy_tickets(Origin::signed(acct), 2));
+		Lottery::on_initialize(10);
+		assert_ok!(Prediction::create_round(Origin::root(), DOT, 2, 5));
+		assert_ok!(Prediction::stake(Origin::signed(acct), 0, 1, 1000));
+		Prediction::on_initialize(6);
+	});
+	// an answer for another index
+	harness
+		.reply("?lottery=0&index=0", 200, &seed_reply(&signed(Subject::Lottery(0), 1, &[2; 32])))
+		.reply("?prediction=0&index=1", 200, &seed_reply(&signed(Subject::Prediction(0), 0, &[2; 32])));
+	harness.execute_with(|| {
+		assert!(matches!(OcwDemo::draw(0), Err(Error::<Test>::ConvertionError)));
+		assert!(matches!(OcwDemo::resolve(0), Err(Error::<Test>::ConvertionError)));
+		// a round that doesn't exist has no seed to ask for
+		assert!(matches!(OcwDemo::resolve(1), Err(Error::<Test>::ConvertionError)));
+	});
+	assert!(harness.take_calls().is_empty());
+	harness.assert_done();
+}
+
+#[test]
+fn test_harness_already_fetched_while_lock_is_held() {
+	let mut harness = OracleHarness::new();
+	harness.execute_with(place_bet);
+	let lock = StorageValueRef::persistent(b"ocw-postgres::lock");
+
+	// another worker holds the lock: no request is made
+	harness.execute_with(|| {
+		lock.set(&true);
+		System::set_block_number(3);
+		let bet = Chance::scheduled_bet()[0].clone();
+		assert_ok!(OcwDemo::fetch_if_needed(bet));
+		assert_eq!(lock.get::<bool>(), Some(Some(true)));
+	});
+	harness.run_blocks(3..=4);
+	assert!(harness.take_calls().is_empty());
+	harness.assert_done();
+	harness.execute_with(|| assert_eq!(lock.get::<bool>(), Some(Some(true))));
+
+	// once it is released the bet is settled
+	harness.execute_with(|| lock.set(&false));
+	harness.reply("?bet=0&index=0", 200, &seed_reply(&signed(Subject::Bet(0), 0, &[2; 32])));
+	harness.run_blocks(5..=5);
+	assert_eq!(harness.take_calls().len(), 1);
+	harness.assert_done();
+}
+
+#[test]
+fn test_harness_submit_error_without_keys() {
+	let mut harness = OracleHarness::without_keys();
+	let acct: <Test as system::Trait>::AccountId = Default::default();
+	harness.execute_with(|| {
+		place_bet();
+		assert_ok!(Prediction::create_round(Origin::root(), DOT, 2, 5));
+		assert_ok!(Prediction::stake(Origin::signed(acct), 0, 1, 1000));
+		Prediction::on_initialize(6);
+	});
+	harness.execute_with(|| {
+		System::set_block_number(3);
+		// bets are not fetched without a key to submit them with
+		let bet = Chance::scheduled_bet()[0].clone();
+		assert!(matches!(OcwDemo::fetch_if_needed(bet), Err(Error::<Test>::SubmitError)));
+		assert_eq!(StorageValueRef::persistent(b"ocw-postgres::lock").get::<bool>(), Some(Some(false)));
+	});
+	harness.execute_with(|| {
+		assert!(matches!(OcwDemo::resolve(0), Err(Error::<Test>::SubmitError)));
+		assert_ok!(Lottery::buy_tickets(Origin::signed(acct), 2));
+		Lottery::on_initialize(10);
+	});
+	harness.execute_with(|| assert!(matches!(OcwDemo::draw(0), Err(Error::<Test>::SubmitError))));
+	assert!(harness.take_calls().is_empty());
+	harness.assert_done();
+}
+
+#[test]
+fn test_harness_draws_lottery_with_the_round_seed() {
+	let mut harness = OracleHarness::new();
+	let acct: <Test as system::Trait>::AccountId = Default::default();
+	harness.execute_with(|| {
+		assert_ok!(Lottery::buy_tickets(Origin::signed(acct), 2));
+		Lottery::on_initialize(10);
+	});
+	harness.reply("?lottery=0&index=0", 500, "");
+	harness.run_blocks(10..=10);
+	assert!(harness.take_calls().is_empty());
+	harness.assert_done();
+
+	// the round stays pending and is drawn by a later worker
+	let reveal = signed(Subject::Lottery(0), 0, &[4; 32]);
+	harness.reply("?lottery=0&index=0", 200, &seed_reply(&reveal));
+	harness.run_blocks(11..=11);
+	assert_eq!(harness.take_calls(), vec![Call::submit_draw(0, reveal)]);
+	harness.assert_done();
+}
+
+#[test]
+fn test_harness_resolves_predictions_with_their_round_seeds() {
+	let mut harness = OracleHarness::new();
+	let acct: <Test as system::Trait>::AccountId = Default::default();
+	harness.execute_with(|| {
+		for round_id in 0..2 {
+			assert_ok!(Prediction::create_round(Origin::root(), DOT, 2, 5));
+			assert_ok!(Prediction::stake(Origin::signed(acct), round_id, 1, 1000));
+		}
+		Prediction::on_initialize(6);
+		assert_eq!(Prediction::pending_resolutions(), vec![0, 1]);
+	});
+	let first = signed(Subject::Prediction(0), 0, &[1; 32]);
+	let second = signed(Subject::Prediction(1), 1, &[0; 32]);
+	harness
+		.reply("?prediction=0&index=0", 200, &seed_reply(&first))
+		.reply("?prediction=1&index=1", 200, &seed_reply(&second));
+	harness.run_blocks(6..=6);
+	assert_eq!(
+		harness.take_calls(),
+		vec![Call::submit_resolution(0, first), Call::submit_resolution(1, second)],
+	);
+	harness.assert_done();
+}
+
 type AccountPublic = <Signature as Verify>::Signer;
 
 /// Generate an account ID from seed.
@@ -1,16 +1,22 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{    
-traits::{Currency, ExistenceRequirement::AllowDeath},
-Parameter, decl_module, decl_event, decl_storage, decl_error, ensure, dispatch
+use frame_support::{
+Parameter, decl_module, decl_event, decl_storage, decl_error, ensure, dispatch, traits::Get, weights::Weight,
 };
 use frame_system::{self as system, ensure_signed};
+use orml_traits::MultiCurrency;
 
 use sp_runtime::{
     traits::{Member, AtLeast32Bit, AtLeast32BitUnsigned, Zero, StaticLookup, AccountIdConversion},
-    ModuleId
+    ModuleId, RuntimeDebug
 };
+use codec::{Decode, Encode};
+use sp_std::prelude::*;
+
+pub mod migrations;
+
+use migrations::Releases;
 
 #[cfg(test)]
 mod mock;
@@ -29,48 +35,76 @@ pub trait Trait: frame_system::Trait {
 	/// The arithmetic type of asset identifier.
 	type AssetId: Parameter + AtLeast32Bit + Default + Copy;
 
-    type Currency: Currency<Self::AccountId>;
+	/// The currencies LPs can pool. Each pool is denominated in a single `CurrencyId`.
+    type Currency: MultiCurrency<Self::AccountId>;
+
+	/// The currency of the single pool that existed before pools were keyed by currency.
+	type LegacyCurrencyId: Get<CurrencyIdOf<Self>>;
 
 }
 
+/// The liquidity of one pool.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct PoolStatus<Balance> {
+	/// The funds held by the pool.
+	pub reserves: Balance,
+	/// The LP units issued against them.
+	pub total_supply: Balance,
+}
+
 type AccountIdOf<T> = <T as system::Trait>::AccountId;
-type BalanceOf<T> = <<T as Trait>::Currency as Currency<AccountIdOf<T>>>::Balance;
+type BalanceOf<T> = <<T as Trait>::Currency as MultiCurrency<AccountIdOf<T>>>::Balance;
+pub type CurrencyIdOf<T> = <<T as Trait>::Currency as MultiCurrency<AccountIdOf<T>>>::CurrencyId;
 
 decl_module! {
 	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
 		type Error = Error<T>;
 
 		fn deposit_event() = default;
+
+		fn on_runtime_upgrade() -> Weight {
+			#[cfg(feature = "try-runtime")]
+			let snapshot = migrations::pre_migrate::<T>();
+			let weight = migrations::migrate::<T>();
+			#[cfg(feature = "try-runtime")]
+			{
+				if let Ok(snapshot) = snapshot {
+					migrations::post_migrate::<T>(snapshot).expect("pooler migration checks failed");
+				}
+			}
+			weight
+		}
 		
 		
 		#[weight = 0]
 		fn transfer(origin,
+			currency_id: CurrencyIdOf<T>,
 			target: <T::Lookup as StaticLookup>::Source,
 			#[compact] amount: BalanceOf<T>
 		) {
 			let who = ensure_signed(origin)?;
-			let origin_balance = <Balances<T>>::get(&who);
+			let origin_balance = <Balances<T>>::get(currency_id, &who);
 			let target = T::Lookup::lookup(target)?;
 			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
 			ensure!(origin_balance >= amount, Error::<T>::BalanceLow);
 
 			// Self::deposit_event(RawEvent::Transferred(&who, &target, amount));
-			<Balances<T>>::insert(who, origin_balance - amount);
-			<Balances<T>>::mutate(target, |balance| *balance += amount);
+			<Balances<T>>::insert(currency_id, who, origin_balance - amount);
+			<Balances<T>>::mutate(currency_id, target, |balance| *balance += amount);
 		}
 
 		#[weight = 0]
-		pub fn deposit(origin, amount: BalanceOf<T>) -> dispatch::DispatchResult { 
+		pub fn deposit(origin, currency_id: CurrencyIdOf<T>, amount: BalanceOf<T>) -> dispatch::DispatchResult {
 			let who = ensure_signed(origin)?;
-			Self::mint(who, amount)?;
+			Self::mint(currency_id, who, amount)?;
 			//TODO add event
 			Ok(())
 
 		}
 		#[weight = 0]
-		pub fn withdraw(origin, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+		pub fn withdraw(origin, currency_id: CurrencyIdOf<T>, amount: BalanceOf<T>) -> dispatch::DispatchResult {
 			let who = ensure_signed(origin)?;
-			Self::burn(who, amount)?;
+			Self::burn(currency_id, who, amount)?;
 			// TODO add event
 			Ok(())
 
@@ -106,51 +140,79 @@ decl_error! {
 
 decl_storage! {
 	trait Store for Module<T: Trait> as pooler {
-		/// The number of units of pooler held by any given account.
-		Balances: map hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
-		/// The next asset identifier up for grabs.
+		/// The number of units of pooler held by any given account, per pool currency.
 		///
-		/// TWOX-NOTE: `AssetId` is trusted, so this is safe.
-		TotalSupply get(fn total_supply): BalanceOf<T>;
+		/// TWOX-NOTE: `CurrencyId` is trusted, so this is safe.
+		Balances: double_map hasher(twox_64_concat) CurrencyIdOf<T>, hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+		/// The total units of pooler issued for each pool currency.
+		TotalSupply get(fn total_supply): map hasher(twox_64_concat) CurrencyIdOf<T> => BalanceOf<T>;
+		/// The storage layout in use. New chains start at the latest one.
+		StorageVersion build(|_| Releases::V2): Releases;
+	}
+	add_extra_genesis {
+		/// LP positions, as `(currency_id, who, amount)`. Each is deposited from `who`'s genesis
+		/// balance like a `deposit` call.
+		config(positions): Vec<(CurrencyIdOf<T>, T::AccountId, BalanceOf<T>)>;
+		/// Reserves issued straight into the pool of a currency without minting units, as
+		/// `(currency_id, amount)`. They go to whoever holds the pool's units.
+		config(reserves): Vec<(CurrencyIdOf<T>, BalanceOf<T>)>;
+		build(|config: &GenesisConfig<T>| {
+			for (currency_id, who, amount) in &config.positions {
+				<Module<T>>::mint(*currency_id, who.clone(), *amount)
+					.expect("genesis LP positions must be funded");
+			}
+			for (currency_id, amount) in &config.reserves {
+				T::Currency::deposit(*currency_id, &<Module<T>>::account_id(), *amount)
+					.expect("genesis reserves must be issuable");
+			}
+		});
 	}
 }
 
 // The main implementation block for the module.
 impl<T: Trait> Module<T> {
 
-	/// Get the asset `id` balance of `who`.
-	pub fn balance(who: T::AccountId) -> BalanceOf<T> {
-		<Balances<T>>::get(who)
+	/// Get the `currency_id` pool balance of `who`.
+	pub fn balance(currency_id: CurrencyIdOf<T>, who: T::AccountId) -> BalanceOf<T> {
+		<Balances<T>>::get(currency_id, who)
 	}
 
-	pub fn mint(who: T::AccountId, amount: BalanceOf<T>) -> dispatch::DispatchResult{
-		let balance_of_pallet = T::Currency::free_balance(&Self::account_id());
-		T::Currency::transfer(&who, &Self::account_id(), amount, AllowDeath)?;
+	pub fn mint(currency_id: CurrencyIdOf<T>, who: T::AccountId, amount: BalanceOf<T>) -> dispatch::DispatchResult{
+		let balance_of_pallet = T::Currency::free_balance(currency_id, &Self::account_id());
+		T::Currency::transfer(currency_id, &who, &Self::account_id(), amount)?;
 		let payout;
-		let total_supply = Self::total_supply();
+		let total_supply = Self::total_supply(currency_id);
 		if total_supply == 0.into() {
 			payout = amount;
 		} else {
 			payout = amount * total_supply / balance_of_pallet;
 		}
-		<Balances<T>>::mutate(who, |balance| *balance += payout);
-		<TotalSupply<T>>::mutate(|total| *total += payout);
+		<Balances<T>>::mutate(currency_id, who, |balance| *balance += payout);
+		<TotalSupply<T>>::mutate(currency_id, |total| *total += payout);
 		Ok(())
 	}
 
-	pub fn burn(who: T::AccountId, amount: BalanceOf<T>)  -> dispatch::DispatchResult{
-		let origin_balance = <Balances<T>>::get(&who);
+	pub fn burn(currency_id: CurrencyIdOf<T>, who: T::AccountId, amount: BalanceOf<T>)  -> dispatch::DispatchResult{
+		let origin_balance = <Balances<T>>::get(currency_id, &who);
 		ensure!(origin_balance >= amount, Error::<T>::BalanceLow);
-		let total_supply = Self::total_supply();
-		let balance_of_pallet = T::Currency::free_balance(&Self::account_id());
+		let total_supply = Self::total_supply(currency_id);
+		let balance_of_pallet = T::Currency::free_balance(currency_id, &Self::account_id());
 		let payout = amount * balance_of_pallet / total_supply;
-		T::Currency::transfer(&Self::account_id(), &who, payout, AllowDeath)?;		
-		<Balances<T>>::mutate(who, |balance| *balance -= amount);
-		<TotalSupply<T>>::mutate(|total| *total -= amount);
+		T::Currency::transfer(currency_id, &Self::account_id(), &who, payout)?;
+		<Balances<T>>::mutate(currency_id, who, |balance| *balance -= amount);
+		<TotalSupply<T>>::mutate(currency_id, |total| *total -= amount);
 		Ok(())
 
 	}
 
+	/// The reserves and issued units of the `currency_id` pool.
+	pub fn pool_status(currency_id: CurrencyIdOf<T>) -> PoolStatus<BalanceOf<T>> {
+		PoolStatus {
+			reserves: T::Currency::free_balance(currency_id, &Self::account_id()),
+			total_supply: Self::total_supply(currency_id),
+		}
+	}
+
 	pub fn account_id() -> T::AccountId{
         const PALLET_ID: ModuleId = ModuleId(*b"assethdl");
         PALLET_ID.into_account()
@@ -0,0 +1,89 @@
+//! Storage migrations.
+//!
+//! `V1` is the single-currency layout pools had before they were keyed by currency: balances
+//! were a map from account to pool units and the total supply a single value. `V2` moves both
+//! into the pool of `LegacyCurrencyId`.
+//!
+//! With the `try-runtime` feature, `pre_migrate` and `post_migrate` check the state around the
+//! migration on every runtime upgrade.
+
+use crate::*;
+use frame_support::{
+	storage::migration::{take_storage_value, StorageKeyIterator},
+	traits::Get, weights::Weight, Blake2_128Concat,
+};
+use sp_std::prelude::*;
+
+/// The storage layouts of this pallet.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+	/// Single-currency pools.
+	V1,
+	/// Pools keyed by currency.
+	V2,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1
+	}
+}
+
+/// What `post_migrate` checks the migrated state against.
+pub struct Snapshot<Balance> {
+	pub holders: usize,
+	pub units: Balance,
+	pub total_supply: Option<Balance>,
+}
+
+fn legacy_balances<T: Trait>() -> StorageKeyIterator<T::AccountId, BalanceOf<T>, Blake2_128Concat> {
+	StorageKeyIterator::new(b"pooler", b"Balances")
+}
+
+/// Move the `V1` pool into the pool of `LegacyCurrencyId`.
+pub fn migrate<T: Trait>() -> Weight {
+	if StorageVersion::get() != Releases::V1 {
+		return 0;
+	}
+	let currency_id = T::LegacyCurrencyId::get();
+	let balances: Vec<_> = legacy_balances::<T>().drain().collect();
+	let migrated = balances.len() as Weight;
+	for (who, balance) in balances {
+		Balances::<T>::insert(currency_id, who, balance);
+	}
+	if let Some(total) = take_storage_value::<BalanceOf<T>>(b"pooler", b"TotalSupply", &[]) {
+		TotalSupply::<T>::insert(currency_id, total);
+	}
+	StorageVersion::put(Releases::V2);
+	T::DbWeight::get().reads_writes(migrated + 2, 2 * migrated + 3)
+}
+
+/// Record the `V1` pool before migrating.
+#[cfg(any(test, feature = "try-runtime"))]
+pub fn pre_migrate<T: Trait>() -> Result<Snapshot<BalanceOf<T>>, &'static str> {
+	if StorageVersion::get() != Releases::V1 {
+		return Err("pooler storage is already migrated");
+	}
+	let (holders, units) = legacy_balances::<T>()
+		.fold((0, Zero::zero()), |(holders, units): (usize, BalanceOf<T>), (_, balance)| (holders + 1, units + balance));
+	let total_supply = frame_support::storage::migration::get_storage_value::<BalanceOf<T>>(b"pooler", b"TotalSupply", &[]);
+	Ok(Snapshot { holders, units, total_supply })
+}
+
+/// Check every unit of the `V1` pool ended up in the pool of `LegacyCurrencyId`.
+#[cfg(any(test, feature = "try-runtime"))]
+pub fn post_migrate<T: Trait>(snapshot: Snapshot<BalanceOf<T>>) -> Result<(), &'static str> {
+	if StorageVersion::get() != Releases::V2 {
+		return Err("pooler storage version wasn't bumped");
+	}
+	let currency_id = T::LegacyCurrencyId::get();
+	let (holders, units) = Balances::<T>::iter_prefix(currency_id)
+		.fold((0, Zero::zero()), |(holders, units): (usize, BalanceOf<T>), (_, balance)| (holders + 1, units + balance));
+	if holders != snapshot.holders || units != snapshot.units {
+		return Err("pool units were lost");
+	}
+	if snapshot.total_supply.map_or(false, |total| total != Module::<T>::total_supply(currency_id)) {
+		return Err("total supply was lost");
+	}
+	Ok(())
+}
@@ -19,7 +19,7 @@ impl_outer_origin! {
 		pub const MaximumBlockLength: u32 = 2 * 1024;
 		pub const AvailableBlockRatio: Perbill = Perbill::one();
 	}
-	pub type Test_Balances = pallet_balances::Module<Test>;
+	pub type Tokens = orml_tokens::Module<Test>;
 	pub type System = frame_system::Module<Test>;
 
 	impl system::Trait for Test {
@@ -45,24 +45,27 @@ impl_outer_origin! {
 		type Version = ();
 		type PalletInfo = ();
 		// type ModuleToIndex = ();
-		type AccountData = pallet_balances::AccountData<u128>;
+		type AccountData = ();
 		type OnNewAccount = ();
 		type OnKilledAccount = ();
 		type SystemWeightInfo = ();
 	}
 
+	pub type CurrencyId = u32;
+	pub const DOT: CurrencyId = 1;
+	pub const USD: CurrencyId = 2;
+
 	parameter_types! {
-		pub const ExistentialDeposit: u64 = 1;
+		pub const LegacyCurrencyId: CurrencyId = DOT;
 	}
 
-	impl pallet_balances::Trait for Test {
-		type Balance = u128;
+	impl orml_tokens::Trait for Test {
 		type Event = ();
-		type DustRemoval = ();
-		type ExistentialDeposit = ExistentialDeposit;
-		type AccountStore = System;
+		type Balance = u128;
+		type Amount = i128;
+		type CurrencyId = CurrencyId;
+		type OnReceived = ();
 		type WeightInfo = ();
-		type MaxLocks = ();
 	}
 	
 	
@@ -70,15 +73,19 @@ impl_outer_origin! {
 		type Event = ();
 		type Balance = u128;
 		type AssetId = u32;
-		type Currency = Test_Balances;
+		type Currency = Tokens;
+		type LegacyCurrencyId = LegacyCurrencyId;
 	}
 	pub type Pooler = Module<Test>;
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
-	pallet_balances::GenesisConfig::<Test> {
-		balances: vec![(1, 20000000000000), (2, 1000000000000), (3, 9000000000000), (4, 10), (5, 2)],
+	orml_tokens::GenesisConfig::<Test> {
+		endowed_accounts: vec![
+			(1, DOT, 20000000000000), (2, DOT, 1000000000000), (3, DOT, 9000000000000), (4, DOT, 10), (5, DOT, 2),
+			(1, USD, 20000000000000), (2, USD, 1000000000000),
+		],
 	}.assimilate_storage(&mut t).unwrap();
 	let mut ext = sp_io::TestExternalities::new(t);
 	ext.execute_with(|| System::set_block_number(1));
@@ -1,27 +1,29 @@
 use crate::{Error, mock::*};
 use crate::*;
-use frame_support::{assert_ok, assert_noop};
+use frame_support::{assert_ok, assert_noop, storage::migration::put_storage_value, Blake2_128Concat, StorageHasher};
+use codec::Encode;
+use orml_traits::MultiCurrency;
 
 
 
 #[test]
 fn issuing_asset_units_to_issuer_should_work() {
 	new_test_ext().execute_with(|| {
-		assert_eq!(Pooler::balance(1), 0);
+		assert_eq!(Pooler::balance(DOT, 1), 0);
 	});
 }
 
 #[test]
 fn minting_pooler_multiple_times() {
 	new_test_ext().execute_with(|| {				  
-		assert_ok!(Pooler::deposit(Origin::signed(1), 9000000000000));
-		assert_eq!(Pooler::balance(1), 9000000000000);
-		assert_ok!(Pooler::deposit(Origin::signed(2), 1000000000000));
-		assert_eq!(Pooler::balance(2), 1000000000000);
-		assert_eq!(Pooler::total_supply(), 10000000000000);
-		assert_ok!(Pooler::transfer(Origin::signed(1), 6, Pooler::balance(1)));
-		assert_ok!(Pooler::withdraw(Origin::signed(6), Pooler::balance(6)));
-		assert_eq!(Test_Balances::free_balance(6), 9000000000000);
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 9000000000000));
+		assert_eq!(Pooler::balance(DOT, 1), 9000000000000);
+		assert_ok!(Pooler::deposit(Origin::signed(2), DOT, 1000000000000));
+		assert_eq!(Pooler::balance(DOT, 2), 1000000000000);
+		assert_eq!(Pooler::total_supply(DOT), 10000000000000);
+		assert_ok!(Pooler::transfer(Origin::signed(1), DOT, 6, Pooler::balance(DOT, 1)));
+		assert_ok!(Pooler::withdraw(Origin::signed(6), DOT, Pooler::balance(DOT, 6)));
+		assert_eq!(Tokens::free_balance(DOT, &6), 9000000000000);
 
 	});
 }
@@ -29,21 +31,21 @@ fn minting_pooler_multiple_times() {
 #[test]
 fn minting_burning_pooler_multiple_times_fee_accumilation() {
 	new_test_ext().execute_with(|| {				  
-		assert_ok!(Pooler::deposit(Origin::signed(1), 9000000000000));
-		assert_ok!(Test_Balances::transfer(Origin::signed(3), Pooler::account_id(), 9000000000000));
-		assert_eq!(Pooler::balance(1), 9000000000000);
-		assert_ok!(Pooler::deposit(Origin::signed(2), 1000000000000));
-		assert_eq!(Pooler::balance(2), 500000000000);
-		assert_eq!(Pooler::total_supply(), 9500000000000);
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 9000000000000));
+		assert_ok!(Tokens::transfer(Origin::signed(3), Pooler::account_id(), DOT, 9000000000000));
+		assert_eq!(Pooler::balance(DOT, 1), 9000000000000);
+		assert_ok!(Pooler::deposit(Origin::signed(2), DOT, 1000000000000));
+		assert_eq!(Pooler::balance(DOT, 2), 500000000000);
+		assert_eq!(Pooler::total_supply(DOT), 9500000000000);
 
-		assert_ok!(Pooler::transfer(Origin::signed(1), 6, Pooler::balance(1)));
-		assert_ok!(Pooler::withdraw(Origin::signed(6), Pooler::balance(6)));
-		assert_eq!(Test_Balances::free_balance(6), 18000000000000);
+		assert_ok!(Pooler::transfer(Origin::signed(1), DOT, 6, Pooler::balance(DOT, 1)));
+		assert_ok!(Pooler::withdraw(Origin::signed(6), DOT, Pooler::balance(DOT, 6)));
+		assert_eq!(Tokens::free_balance(DOT, &6), 18000000000000);
 
-		assert_eq!(Pooler::total_supply(), 500000000000);
+		assert_eq!(Pooler::total_supply(DOT), 500000000000);
 
-		assert_ok!(Pooler::withdraw(Origin::signed(2), Pooler::balance(2)));
-		assert_eq!(Test_Balances::free_balance(2), 1000000000000);
+		assert_ok!(Pooler::withdraw(Origin::signed(2), DOT, Pooler::balance(DOT, 2)));
+		assert_eq!(Tokens::free_balance(DOT, &2), 1000000000000);
 
 	});
 }
@@ -52,59 +54,120 @@ fn minting_burning_pooler_multiple_times_fee_accumilation() {
 #[test]
 fn querying_total_supply_should_work() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Pooler::deposit(Origin::signed(1), 100));
-		assert_eq!(Pooler::balance(1), 100);
-		assert_ok!(Pooler::transfer(Origin::signed(1), 2, 50));
-		assert_eq!(Pooler::balance(1), 50);
-		assert_eq!(Pooler::balance(2), 50);
-		assert_ok!(Pooler::transfer(Origin::signed(2), 3, 31));
-		assert_eq!(Pooler::balance(1), 50);
-		assert_eq!(Pooler::balance(2), 19);
-		assert_eq!(Pooler::balance(3), 31);
-		assert_eq!(Pooler::total_supply(), 100);
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100));
+		assert_eq!(Pooler::balance(DOT, 1), 100);
+		assert_ok!(Pooler::transfer(Origin::signed(1), DOT, 2, 50));
+		assert_eq!(Pooler::balance(DOT, 1), 50);
+		assert_eq!(Pooler::balance(DOT, 2), 50);
+		assert_ok!(Pooler::transfer(Origin::signed(2), DOT, 3, 31));
+		assert_eq!(Pooler::balance(DOT, 1), 50);
+		assert_eq!(Pooler::balance(DOT, 2), 19);
+		assert_eq!(Pooler::balance(DOT, 3), 31);
+		assert_eq!(Pooler::total_supply(DOT), 100);
 	});
 }
 
 #[test]
 fn transferring_amount_above_available_balance_should_work() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Pooler::deposit(Origin::signed(1), 100));
-		assert_eq!(Pooler::balance(1), 100);
-		assert_ok!(Pooler::transfer(Origin::signed(1), 2, 50));
-		assert_eq!(Pooler::balance(1), 50);
-		assert_eq!(Pooler::balance(2), 50);
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100));
+		assert_eq!(Pooler::balance(DOT, 1), 100);
+		assert_ok!(Pooler::transfer(Origin::signed(1), DOT, 2, 50));
+		assert_eq!(Pooler::balance(DOT, 1), 50);
+		assert_eq!(Pooler::balance(DOT, 2), 50);
 	});
 }
 
 #[test]
 fn transferring_amount_more_than_available_balance_should_not_work() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Pooler::deposit(Origin::signed(1), 100));
-		assert_eq!(Pooler::balance(1), 100);
-		assert_ok!(Pooler::transfer(Origin::signed(1), 2, 50));
-		assert_eq!(Pooler::balance(1), 50);
-		assert_eq!(Pooler::balance(2), 50);
-		assert_ok!(Pooler::withdraw(Origin::signed(1), 50));
-		assert_eq!(Pooler::balance(1), 0);
-		assert_noop!(Pooler::transfer(Origin::signed(1), 1, 50), Error::<Test>::BalanceLow);
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100));
+		assert_eq!(Pooler::balance(DOT, 1), 100);
+		assert_ok!(Pooler::transfer(Origin::signed(1), DOT, 2, 50));
+		assert_eq!(Pooler::balance(DOT, 1), 50);
+		assert_eq!(Pooler::balance(DOT, 2), 50);
+		assert_ok!(Pooler::withdraw(Origin::signed(1), DOT, 50));
+		assert_eq!(Pooler::balance(DOT, 1), 0);
+		assert_noop!(Pooler::transfer(Origin::signed(1), DOT, 1, 50), Error::<Test>::BalanceLow);
 	});
 }
 
 #[test]
 fn transferring_less_than_one_unit_should_not_work() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Pooler::deposit(Origin::signed(1), 100));
-		assert_eq!(Pooler::balance(1), 100);
-		assert_noop!(Pooler::transfer(Origin::signed(1), 2, 0), Error::<Test>::AmountZero);
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100));
+		assert_eq!(Pooler::balance(DOT, 1), 100);
+		assert_noop!(Pooler::transfer(Origin::signed(1), DOT, 2, 0), Error::<Test>::AmountZero);
 	});
 }
 
 #[test]
 fn transferring_more_units_than_total_supply_should_not_work() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Pooler::deposit(Origin::signed(1), 100));
-		assert_eq!(Pooler::balance(1), 100);
-		assert_noop!(Pooler::transfer(Origin::signed(1), 2, 101), Error::<Test>::BalanceLow);
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100));
+		assert_eq!(Pooler::balance(DOT, 1), 100);
+		assert_noop!(Pooler::transfer(Origin::signed(1), DOT, 2, 101), Error::<Test>::BalanceLow);
 	});
 }
 
+#[test]
+fn pools_are_separate_per_currency() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Pooler::deposit(Origin::signed(1), DOT, 100));
+		assert_ok!(Pooler::deposit(Origin::signed(1), USD, 300));
+		assert_eq!(Pooler::balance(DOT, 1), 100);
+		assert_eq!(Pooler::balance(USD, 1), 300);
+		assert_eq!(Pooler::total_supply(DOT), 100);
+		assert_eq!(Pooler::total_supply(USD), 300);
+		assert_ok!(Pooler::withdraw(Origin::signed(1), USD, 300));
+		assert_eq!(Tokens::free_balance(USD, &1), 20000000000000);
+		assert_eq!(Tokens::free_balance(DOT, &Pooler::account_id()), 100);
+		assert_noop!(Pooler::withdraw(Origin::signed(1), USD, 1), Error::<Test>::BalanceLow);
+	});
+}
+
+#[test]
+fn migrating_v1_moves_the_pool_to_the_legacy_currency() {
+	new_test_ext().execute_with(|| {
+		for (who, units) in &[(1u64, 70u128), (2, 30)] {
+			put_storage_value(b"pooler", b"Balances", &Blake2_128Concat::hash(&who.encode()), *units);
+		}
+		put_storage_value(b"pooler", b"TotalSupply", &[], 100u128);
+
+		let snapshot = migrations::pre_migrate::<Test>().unwrap();
+		migrations::migrate::<Test>();
+		assert_ok!(migrations::post_migrate::<Test>(snapshot));
+
+		assert_eq!(Pooler::balance(DOT, 1), 70);
+		assert_eq!(Pooler::balance(DOT, 2), 30);
+		assert_eq!(Pooler::total_supply(DOT), 100);
+		assert_eq!(Pooler::total_supply(USD), 0);
+		assert_eq!(StorageVersion::get(), Releases::V2);
+		// Migrating again is a no-op.
+		assert_eq!(migrations::migrate::<Test>(), 0);
+		assert!(migrations::pre_migrate::<Test>().is_err());
+	});
+}
+
+#[test]
+fn genesis_positions_and_reserves_fund_the_pool() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	orml_tokens::GenesisConfig::<Test> {
+		endowed_accounts: vec![(1, DOT, 1000), (2, DOT, 1000)],
+	}.assimilate_storage(&mut t).unwrap();
+	GenesisConfig::<Test> {
+		positions: vec![(DOT, 1, 300), (DOT, 2, 100)],
+		reserves: vec![(DOT, 400)],
+	}.assimilate_storage(&mut t).unwrap();
+	sp_io::TestExternalities::new(t).execute_with(|| {
+		assert_eq!(Pooler::balance(DOT, 1), 300);
+		assert_eq!(Pooler::balance(DOT, 2), 100);
+		assert_eq!(Pooler::total_supply(DOT), 400);
+		assert_eq!(Tokens::free_balance(DOT, &1), 700);
+		assert_eq!(Tokens::free_balance(DOT, &Pooler::account_id()), 800);
+		assert_eq!(StorageVersion::get(), Releases::V2);
+		// The reserves are shared by the LPs.
+		assert_ok!(Pooler::withdraw(Origin::signed(2), DOT, 100));
+		assert_eq!(Tokens::free_balance(DOT, &2), 1100);
+	});
+}
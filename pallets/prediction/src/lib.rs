@@ -0,0 +1,291 @@
+//! Pari-mutuel prediction rounds.
+//!
+//! A round offers a fixed number of outcomes and takes stakes until it closes. Once closed, it is
+//! assigned an oracle seed index, and once the closing block is built a settler submits the seed
+//! the oracle signed for it. The winning outcome is drawn on chain with
+//! `pallet_chance_fairness::draw` from the seed mixed with the closing block's hash. Stakes
+//! count towards the staker's gaming limits like bets. The stakes on the losing
+//! outcomes, less `ProtocolFee`, are shared among the winners in proportion to their stakes, so
+//! the LP pool carries no exposure. If nobody backed the winning outcome every stake is refunded.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure,
+	traits::{EnsureOrigin, Get}, transactional, weights::Weight,
+};
+use frame_system::{self as system, ensure_signed};
+use orml_traits::MultiCurrency;
+use pallet_admin as admin;
+use pallet_chance::{self as chance, BalanceOf, CurrencyIdOf, Reveal, Subject};
+use pallet_chance_fairness as fairness;
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational,
+	traits::{AccountIdConversion, Saturating, Zero},
+	ModuleId, Perbill, RuntimeDebug,
+};
+use sp_std::{convert::TryInto, prelude::*};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub type RoundId = u32;
+
+/// The index of one of the outcomes of a round.
+pub type Outcome = u32;
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Round<CurrencyId, BlockNumber> {
+	pub currency_id: CurrencyId,
+	/// The number of outcomes; valid outcomes are `0..outcomes`.
+	pub outcomes: u32,
+	/// The first block stakes are no longer taken.
+	pub closes_at: BlockNumber,
+}
+
+pub type RoundOf<T> = Round<CurrencyIdOf<T>, <T as system::Trait>::BlockNumber>;
+
+pub trait Trait: system::Trait + chance::Trait {
+	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// The origin allowed to open rounds.
+	type CreateOrigin: EnsureOrigin<Self::Origin>;
+	/// The share of the losing stakes paid to the treasury.
+	type ProtocolFee: Get<Perbill>;
+	type MaxOutcomes: Get<u32>;
+	type MaxStakersPerRound: Get<u32>;
+}
+
+decl_storage! {
+	trait Store for Module<T: Trait> as Prediction {
+		NextRoundId get(fn next_round_id): RoundId;
+		Rounds get(fn round): map hasher(twox_64_concat) RoundId => Option<RoundOf<T>>;
+		/// The rounds closing at a block.
+		Closing get(fn closing): map hasher(twox_64_concat) T::BlockNumber => Vec<RoundId>;
+		/// Everyone who staked on a round, in the order they first staked.
+		Stakers get(fn stakers): map hasher(twox_64_concat) RoundId => Vec<T::AccountId>;
+		Stakes get(fn stake_of):
+			double_map hasher(twox_64_concat) RoundId, hasher(blake2_128_concat) (T::AccountId, Outcome)
+			=> BalanceOf<T>;
+		/// The total staked on each outcome of a round.
+		OutcomeStakes get(fn outcome_stake):
+			double_map hasher(twox_64_concat) RoundId, hasher(twox_64_concat) Outcome => BalanceOf<T>;
+		/// Closed rounds waiting for the offchain worker to pick their outcome.
+		PendingResolutions get(fn pending_resolutions): Vec<RoundId>;
+		/// The winning outcome of each resolved round.
+		Results get(fn result): map hasher(twox_64_concat) RoundId => Option<Outcome>;
+		/// The oracle seed each round was resolved with and the hash of the block it closed in, so
+		/// anyone can redraw its outcome.
+		ResolutionSeeds get(fn resolution_seed): map hasher(twox_64_concat) RoundId => Option<(Reveal, T::Hash)>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where
+		AccountId = <T as system::Trait>::AccountId,
+		Balance = BalanceOf<T>,
+		BlockNumber = <T as system::Trait>::BlockNumber,
+		CurrencyId = CurrencyIdOf<T>,
+	{
+		/// \[round, currency_id, outcomes, closes_at\]
+		RoundCreated(RoundId, CurrencyId, u32, BlockNumber),
+		/// \[round, who, outcome, amount\]
+		Staked(RoundId, AccountId, Outcome, Balance),
+		/// A round stopped taking stakes. \[round, total_staked\]
+		RoundClosed(RoundId, Balance),
+		/// \[round, winning_outcome, protocol_fee\]
+		RoundResolved(RoundId, Outcome, Balance),
+		/// \[round, who, amount\]
+		PaidOut(RoundId, AccountId, Balance),
+	}
+);
+
+decl_error! {
+	pub enum Error for Module<T: Trait> {
+		/// A round needs between two and `MaxOutcomes` outcomes.
+		InvalidOutcomeCount,
+		/// A round must stay open for at least one block.
+		ZeroDuration,
+		UnknownRound,
+		/// The round no longer takes stakes.
+		RoundClosed,
+		/// The outcome isn't one of the round's outcomes.
+		InvalidOutcome,
+		ZeroStake,
+		/// The round has reached `MaxStakersPerRound`.
+		TooManyStakers,
+		/// The round isn't waiting to be resolved.
+		NotPendingResolution,
+		/// The block the round closed in hasn't been built yet.
+		ResolutionNotReady,
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		const ProtocolFee: Perbill = T::ProtocolFee::get();
+
+		const MaxOutcomes: u32 = T::MaxOutcomes::get();
+
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut weight = T::DbWeight::get().reads_writes(1, 1);
+			for round_id in Closing::<T>::take(now) {
+				weight = weight.saturating_add(Self::close_round(round_id));
+			}
+			weight
+		}
+
+		/// Open a round on `outcomes` outcomes taking stakes for `duration` blocks.
+		#[weight = 0]
+		pub fn create_round(origin, currency_id: CurrencyIdOf<T>, outcomes: u32, duration: T::BlockNumber) {
+			T::CreateOrigin::ensure_origin(origin)?;
+			ensure!(outcomes >= 2 && outcomes <= T::MaxOutcomes::get(), Error::<T>::InvalidOutcomeCount);
+			ensure!(!duration.is_zero(), Error::<T>::ZeroDuration);
+
+			let round_id = Self::next_round_id();
+			NextRoundId::put(round_id.wrapping_add(1));
+			let closes_at = <system::Module<T>>::block_number().saturating_add(duration);
+			Rounds::<T>::insert(round_id, Round { currency_id, outcomes, closes_at });
+			Closing::<T>::mutate(closes_at, |rounds| rounds.push(round_id));
+			Self::deposit_event(RawEvent::RoundCreated(round_id, currency_id, outcomes, closes_at));
+		}
+
+		/// Stake `amount` on `outcome` of an open round.
+		#[weight = 0]
+		pub fn stake(origin, round_id: RoundId, outcome: Outcome, amount: BalanceOf<T>) {
+			let who = ensure_signed(origin)?;
+			let round = Self::round(round_id).ok_or(Error::<T>::UnknownRound)?;
+			ensure!(<system::Module<T>>::block_number() < round.closes_at, Error::<T>::RoundClosed);
+			ensure!(outcome < round.outcomes, Error::<T>::InvalidOutcome);
+			ensure!(!amount.is_zero(), Error::<T>::ZeroStake);
+
+			let mut stakers = Self::stakers(round_id);
+			let new_staker = !stakers.contains(&who);
+			if new_staker {
+				ensure!((stakers.len() as u32) < T::MaxStakersPerRound::get(), Error::<T>::TooManyStakers);
+			}
+			<chance::Module<T>>::ensure_within_gaming_limits(&who, round.currency_id, amount)?;
+			<T as chance::Trait>::Currency::transfer(round.currency_id, &who, &Self::account_id(), amount)?;
+			<chance::Module<T>>::note_gaming_stake(&who, round.currency_id, amount);
+
+			if new_staker {
+				stakers.push(who.clone());
+				Stakers::<T>::insert(round_id, stakers);
+			}
+			Stakes::<T>::mutate(round_id, (who.clone(), outcome), |s| *s = s.saturating_add(amount));
+			OutcomeStakes::<T>::mutate(round_id, outcome, |s| *s = s.saturating_add(amount));
+			Self::deposit_event(RawEvent::Staked(round_id, who, outcome, amount));
+		}
+	}
+}
+
+impl<T: Trait> Module<T> {
+	/// The account holding the stakes of every open round.
+	pub fn account_id() -> T::AccountId {
+		ModuleId(*b"py/prdct").into_account()
+	}
+
+	/// The total staked on `round_id`.
+	pub fn total_staked(round_id: RoundId) -> BalanceOf<T> {
+		let outcomes = Self::round(round_id).map_or(0, |round| round.outcomes);
+		(0..outcomes).fold(Zero::zero(), |total: BalanceOf<T>, outcome| {
+			total.saturating_add(Self::outcome_stake(round_id, outcome))
+		})
+	}
+
+	/// The winning outcome of `round_id` drawn from `seed` and the hash of the block the round
+	/// closed in.
+	pub fn winning_outcome(round_id: RoundId, outcomes: u32, seed: &[u8], block_hash: &[u8]) -> Outcome {
+		let seed = fairness::round_seed(seed, block_hash);
+		fairness::draw(&seed, Subject::Prediction(round_id), 0, outcomes)
+	}
+
+	/// The share of `pool` won by `stake` out of the `winning_total` staked on the winning outcome,
+	/// rounded down.
+	fn share_of(pool: BalanceOf<T>, stake: BalanceOf<T>, winning_total: BalanceOf<T>) -> BalanceOf<T> {
+		let pool = TryInto::<u128>::try_into(pool).unwrap_or(u128::max_value());
+		let stake = TryInto::<u128>::try_into(stake).unwrap_or(u128::max_value());
+		let winning_total = TryInto::<u128>::try_into(winning_total).unwrap_or(u128::max_value());
+		// the stake is part of the winning total, so the share never exceeds the pool
+		multiply_by_rational(pool, stake, winning_total).ok()
+			.and_then(|share| share.try_into().ok())
+			.unwrap_or_else(Zero::zero)
+	}
+
+	/// Settle `round_id` on the outcome drawn from the seed the oracle signed for it and pay out
+	/// its stakes.
+	#[transactional]
+	pub fn resolve_callback(origin: T::Origin, round_id: RoundId, reveal: Reveal) -> DispatchResult {
+		<admin::Module<T>>::ensure_settler(origin)?;
+		ensure!(!<admin::Module<T>>::is_paused(admin::Operation::Settle), chance::Error::<T>::SettlementPaused);
+		let pos = Self::pending_resolutions().iter().position(|r| *r == round_id)
+			.ok_or(Error::<T>::NotPendingResolution)?;
+		let round = Self::round(round_id).ok_or(Error::<T>::UnknownRound)?;
+		ensure!(<system::Module<T>>::block_number() > round.closes_at, Error::<T>::ResolutionNotReady);
+		<chance::Module<T>>::check_reveal(Subject::Prediction(round_id), &reveal)?;
+		let block_hash = <system::Module<T>>::block_hash(round.closes_at);
+		let winning = Self::winning_outcome(round_id, round.outcomes, &reveal.seed, block_hash.as_ref());
+
+		PendingResolutions::mutate(|pending| {
+			pending.remove(pos);
+		});
+		let total = Self::total_staked(round_id);
+		let winning_total = Self::outcome_stake(round_id, winning);
+		let losing = total.saturating_sub(winning_total);
+		let fee = if winning_total.is_zero() { Zero::zero() } else { T::ProtocolFee::get() * losing };
+		let pool = losing - fee;
+		let mut paid: BalanceOf<T> = Zero::zero();
+		for who in Self::stakers(round_id) {
+			let payout = if winning_total.is_zero() {
+				(0..round.outcomes).fold(Zero::zero(), |sum: BalanceOf<T>, outcome| {
+					sum.saturating_add(Self::stake_of(round_id, (who.clone(), outcome)))
+				})
+			} else {
+				let stake = Self::stake_of(round_id, (who.clone(), winning));
+				stake.saturating_add(Self::share_of(pool, stake, winning_total))
+			};
+			if payout.is_zero() {
+				continue;
+			}
+			<T as chance::Trait>::Currency::transfer(round.currency_id, &Self::account_id(), &who, payout)?;
+			<chance::Module<T>>::note_gaming_return(&who, round.currency_id, payout);
+			paid = paid.saturating_add(payout);
+			Self::deposit_event(RawEvent::PaidOut(round_id, who, payout));
+		}
+		// the fee and any rounding dust go to the treasury
+		let remainder = total.saturating_sub(paid);
+		if !remainder.is_zero() {
+			let treasury = <chance::Module<T>>::treasury_account();
+			<T as chance::Trait>::Currency::transfer(round.currency_id, &Self::account_id(), &treasury, remainder)?;
+		}
+
+		Stakers::<T>::remove(round_id);
+		Stakes::<T>::remove_prefix(round_id);
+		OutcomeStakes::<T>::remove_prefix(round_id);
+		Results::insert(round_id, winning);
+		ResolutionSeeds::<T>::insert(round_id, (reveal, block_hash));
+		<chance::Module<T>>::release_seed_index(Subject::Prediction(round_id));
+		Self::deposit_event(RawEvent::RoundResolved(round_id, winning, fee));
+		Ok(())
+	}
+
+	fn close_round(round_id: RoundId) -> Weight {
+		let outcomes = Self::round(round_id).map_or(0, |round| round.outcomes);
+		let total = Self::total_staked(round_id);
+		if Self::stakers(round_id).is_empty() {
+			Rounds::<T>::remove(round_id);
+		} else {
+			<chance::Module<T>>::assign_seed_index(Subject::Prediction(round_id));
+			PendingResolutions::mutate(|pending| pending.push(round_id));
+		}
+		Self::deposit_event(RawEvent::RoundClosed(round_id, total));
+		T::DbWeight::get().reads_writes(5 + u64::from(outcomes), 4)
+	}
+}
@@ -0,0 +1,250 @@
+use crate::{Module, Trait};
+use sp_core::{sr25519, H256, Pair};
+use frame_support::{impl_outer_dispatch, impl_outer_origin, parameter_types, weights::Weight};
+use sp_runtime::{
+	traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
+};
+use frame_system as system;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+impl_outer_dispatch! {
+	pub enum Call for Test where origin: Origin {
+		system::System,
+		pallet_admin::Admin,
+	}
+}
+
+// Configure a mock runtime to test the pallet.
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const MaximumBlockWeight: Weight = 1024;
+	pub const MaximumBlockLength: u32 = 2 * 1024;
+	pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+}
+
+pub type Tokens = orml_tokens::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type System = frame_system::Module<Test>;
+pub type Scheduler = pallet_scheduler::Module<Test>;
+
+impl system::Trait for Test {
+	type BaseCallFilter = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type MaximumBlockWeight = MaximumBlockWeight;
+	type DbWeight = ();
+	type BlockExecutionWeight = ();
+	type ExtrinsicBaseWeight = ();
+	type MaximumExtrinsicWeight = MaximumBlockWeight;
+	type MaximumBlockLength = MaximumBlockLength;
+	type AvailableBlockRatio = AvailableBlockRatio;
+    type Version = ();
+    type PalletInfo = ();
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+}
+
+impl pallet_pooler::Trait for Test {
+    type Event = ();
+    type Balance = u128;
+	type AssetId = u128;
+	type Currency = Tokens;
+	type LegacyCurrencyId = LegacyCurrencyId;
+}
+
+pub type CurrencyId = u32;
+pub const DOT: CurrencyId = 1;
+pub const USD: CurrencyId = 2;
+
+parameter_types! {
+	pub const LegacyCurrencyId: CurrencyId = DOT;
+}
+
+impl orml_tokens::Trait for Test {
+	type Event = ();
+	type Balance = u128;
+	type Amount = i128;
+	type CurrencyId = CurrencyId;
+	type OnReceived = ();
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Trait for Test {
+	type Balance = u64;
+	type Event = ();
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type MaxLocks = ();
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * MaximumBlockWeight::get();
+	pub const MaxScheduledPerBlock: u32 = 10;
+}
+
+impl pallet_scheduler::Trait for Test {
+	type Event = ();
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<u64>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type WeightInfo = ();
+}
+
+pub const SETTLER_BOND: u64 = 100;
+pub const PARAMETER_CHANGE_DELAY: u64 = 3;
+pub const ERA_LENGTH: u64 = 20;
+
+parameter_types! {
+	pub const MaxSettlers: u32  = 10; 
+	pub const MaxRoleMembers: u32 = 5;
+	pub const RemovalDelay: u64 = 5;
+	pub const SettlerBond: u64 = SETTLER_BOND;
+	pub const EraLength: u64 = ERA_LENGTH;
+	pub const MaxMissedSettlements: u32 = 1;
+	pub const MissedSettlementSlash: Perbill = Perbill::from_percent(10);
+	pub const MaxAverageLatency: u64 = 10;
+	pub const MaxDisputes: u32 = 2;
+	pub const ParameterChangeDelay: u64 = PARAMETER_CHANGE_DELAY;
+}
+
+
+impl pallet_admin::Trait for Test {
+	type Event = ();
+	type MaxSettlers = MaxSettlers;
+	type MaxRoleMembers = MaxRoleMembers;
+	type AdminOrigin = frame_system::EnsureRoot<u64>;
+	type RemovalDelay = RemovalDelay;
+	type SettlersChanged = ();
+	type Currency = Balances;
+	type SettlerBond = SettlerBond;
+	type Slash = ();
+	type EraLength = EraLength;
+	type MaxMissedSettlements = MaxMissedSettlements;
+	type MissedSettlementSlash = MissedSettlementSlash;
+	type MaxAverageLatency = MaxAverageLatency;
+	type MaxDisputes = MaxDisputes;
+	type Proposal = Call;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type ParameterChangeDelay = ParameterChangeDelay;
+}
+
+pub const TREASURY: u64 = 99;
+pub const SETTLER: u64 = 7;
+pub const SETTLEMENT_DEADLINE: u64 = 5;
+pub const CANCELLATION_WINDOW: u64 = 2;
+pub const BLOCKS_PER_DAY: u64 = 10;
+pub const LIMIT_INCREASE_COOLDOWN: u64 = 5;
+
+parameter_types! {
+	pub const SystemDecimals: u128  = 100000000000;
+	pub const TreasuryAccount: u64 = TREASURY;
+	pub const TreasuryCut: Perbill = Perbill::from_percent(10);
+	pub const SettlerCut: Perbill = Perbill::from_percent(20);
+	pub const CircuitBreakerWindow: u64 = 10;
+	pub const CircuitBreakerThreshold: Perbill = Perbill::from_percent(40);
+	pub const SettlementDeadline: u64 = SETTLEMENT_DEADLINE;
+	pub const CancellationWindow: u64 = CANCELLATION_WINDOW;
+	pub const CancellationFee: Perbill = Perbill::from_percent(10);
+	pub const MaxBetHistory: u32 = 3;
+	pub const BetHistoryRetention: u64 = 100;
+	pub const BlocksPerDay: u64 = BLOCKS_PER_DAY;
+	pub const LimitIncreaseCooldown: u64 = LIMIT_INCREASE_COOLDOWN;
+	pub const ReferralCut: Perbill = Perbill::from_percent(10);
+	pub const MaxReferralDepth: u32 = 3;
+}
+impl pallet_chance::Trait for Test {
+    type Event = ();
+	type Currency = Tokens;
+	type SystemDecimals = SystemDecimals;
+	type TreasuryAccount = TreasuryAccount;
+	type TreasuryCut = TreasuryCut;
+	type SettlerCut = SettlerCut;
+	type CircuitBreakerWindow = CircuitBreakerWindow;
+	type CircuitBreakerThreshold = CircuitBreakerThreshold;
+	type SettlementDeadline = SettlementDeadline;
+	type CancellationWindow = CancellationWindow;
+	type CancellationFee = CancellationFee;
+	type MaxBetHistory = MaxBetHistory;
+	type BetHistoryRetention = BetHistoryRetention;
+	type BlocksPerDay = BlocksPerDay;
+	type LimitIncreaseCooldown = LimitIncreaseCooldown;
+	type ReferralCut = ReferralCut;
+	type MaxReferralDepth = MaxReferralDepth;
+	type LegacyCurrencyId = LegacyCurrencyId;
+}
+
+parameter_types! {
+	pub const ProtocolFee: Perbill = Perbill::from_percent(10);
+	pub const MaxOutcomes: u32 = 4;
+	pub const MaxStakersPerRound: u32 = 3;
+}
+
+impl Trait for Test {
+	type Event = ();
+	type CreateOrigin = frame_system::EnsureRoot<u64>;
+	type ProtocolFee = ProtocolFee;
+	type MaxOutcomes = MaxOutcomes;
+	type MaxStakersPerRound = MaxStakersPerRound;
+}
+
+pub type Prediction = Module<Test>;
+pub type Pooler = pallet_pooler::Module<Test>;
+pub type Admin = pallet_admin::Module<Test>;
+
+
+/// The key the test oracle signs its seeds with.
+pub fn oracle() -> sr25519::Pair {
+	sr25519::Pair::from_seed(&[9; 32])
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	orml_tokens::GenesisConfig::<Test> {
+		endowed_accounts: vec![
+			(1, DOT, 1000000000000000), (2, DOT, 1000000000000000), (3, DOT, 1000000000000000), (4, DOT, 10), (5, DOT, 2),
+			(1, USD, 1000000000000000), (2, USD, 1000000000000000),
+		],
+	}.assimilate_storage(&mut t).unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(5, 2), (SETTLER, 1000), (8, 1000)],
+	}.assimilate_storage(&mut t).unwrap();
+	pallet_admin::GenesisConfig::<Test> {
+		settlers: vec![SETTLER],
+		..Default::default()
+	}.assimilate_storage(&mut t).unwrap();
+	pallet_chance::GenesisConfig::<Test> {
+		oracle_key: Some(oracle().public()),
+		..Default::default()
+	}.assimilate_storage(&mut t).unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
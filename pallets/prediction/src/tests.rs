@@ -0,0 +1,171 @@
+use crate::{Error, Outcome, RoundId, mock::*};
+use frame_support::{assert_ok, assert_noop, traits::OnInitialize};
+use orml_traits::MultiCurrency;
+use pallet_admin::Operation;
+use pallet_chance::{GamingLimit, LimitPeriod, Reveal, Subject};
+use sp_core::{Pair, H256};
+use sp_runtime::DispatchError;
+
+const INITIAL: u128 = 1000000000000000;
+
+/// The test oracle's reveal of `seed` for prediction round `round_id`.
+fn reveal(round_id: RoundId, seed: Vec<u8>) -> Reveal {
+	let subject = Subject::Prediction(round_id);
+	let index = pallet_chance::Module::<Test>::seed_index(subject).unwrap();
+	let signature = oracle().sign(&pallet_chance_fairness::signed_message(subject, index, &seed));
+	Reveal { index, seed, signature: signature.0.to_vec() }
+}
+
+/// The hash given to the block `round_id` closed in.
+fn closing_hash(round_id: RoundId) -> H256 {
+	H256::repeat_byte(round_id as u8 + 1)
+}
+
+/// Build the block `round_id` closed in and move past it.
+fn build_closing_block(round_id: RoundId) {
+	let closes_at = Prediction::round(round_id).unwrap().closes_at;
+	frame_system::BlockHash::<Test>::insert(closes_at, closing_hash(round_id));
+	System::set_block_number(closes_at + 1);
+}
+
+/// The test oracle's reveal of a seed on which `winning` wins round `round_id`.
+fn reveal_winning(round_id: RoundId, winning: Outcome) -> Reveal {
+	let outcomes = Prediction::round(round_id).unwrap().outcomes;
+	let seed = (0u8..).map(|i| vec![i; 32])
+		.find(|seed| Prediction::winning_outcome(round_id, outcomes, seed, closing_hash(round_id).as_ref()) == winning)
+		.unwrap();
+	reveal(round_id, seed)
+}
+
+#[test]
+fn test_rounds_validate_outcomes_and_stakes() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Prediction::create_round(Origin::signed(1), DOT, 2, 5), DispatchError::BadOrigin);
+		assert_noop!(Prediction::create_round(Origin::root(), DOT, 1, 5), Error::<Test>::InvalidOutcomeCount);
+		assert_noop!(Prediction::create_round(Origin::root(), DOT, 5, 5), Error::<Test>::InvalidOutcomeCount);
+		assert_noop!(Prediction::create_round(Origin::root(), DOT, 2, 0), Error::<Test>::ZeroDuration);
+		assert_ok!(Prediction::create_round(Origin::root(), DOT, 2, 5));
+		assert_eq!(Prediction::round(0).unwrap().closes_at, 6);
+
+		assert_noop!(Prediction::stake(Origin::signed(1), 1, 0, 100), Error::<Test>::UnknownRound);
+		assert_noop!(Prediction::stake(Origin::signed(1), 0, 2, 100), Error::<Test>::InvalidOutcome);
+		assert_noop!(Prediction::stake(Origin::signed(1), 0, 0, 0), Error::<Test>::ZeroStake);
+		assert_ok!(Prediction::stake(Origin::signed(1), 0, 0, 100));
+		assert_ok!(Prediction::stake(Origin::signed(1), 0, 1, 50));
+		assert_ok!(Prediction::stake(Origin::signed(2), 0, 1, 50));
+		assert_ok!(Prediction::stake(Origin::signed(4), 0, 1, 10));
+		assert_noop!(Prediction::stake(Origin::signed(3), 0, 1, 10), Error::<Test>::TooManyStakers);
+		assert_eq!(Prediction::outcome_stake(0, 1), 110);
+		assert_eq!(Prediction::total_staked(0), 210);
+		assert_eq!(Tokens::free_balance(DOT, &Prediction::account_id()), 210);
+
+		System::set_block_number(6);
+		assert_noop!(Prediction::stake(Origin::signed(1), 0, 0, 100), Error::<Test>::RoundClosed);
+	}
+)}
+
+#[test]
+fn test_losing_stakes_are_shared_among_winners() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Prediction::create_round(Origin::root(), DOT, 2, 5));
+		assert_ok!(Prediction::stake(Origin::signed(1), 0, 0, 300));
+		assert_ok!(Prediction::stake(Origin::signed(2), 0, 0, 100));
+		assert_ok!(Prediction::stake(Origin::signed(3), 0, 1, 600));
+		let unsigned = Reveal { index: 0, seed: vec![0; 32], signature: vec![] };
+		assert_noop!(
+			Prediction::resolve_callback(Origin::signed(SETTLER), 0, unsigned.clone()),
+			Error::<Test>::NotPendingResolution
+		);
+
+		Prediction::on_initialize(6);
+		assert_eq!(Prediction::pending_resolutions(), vec![0]);
+		// the round can't be resolved before the block it closed in is built
+		System::set_block_number(6);
+		assert_noop!(
+			Prediction::resolve_callback(Origin::signed(SETTLER), 0, unsigned.clone()),
+			Error::<Test>::ResolutionNotReady
+		);
+		build_closing_block(0);
+		let winning = reveal_winning(0, 0);
+		assert_noop!(
+			Prediction::resolve_callback(Origin::signed(1), 0, winning.clone()),
+			pallet_admin::Error::<Test>::NotSettler
+		);
+		assert_noop!(
+			Prediction::resolve_callback(Origin::signed(SETTLER), 0, unsigned),
+			pallet_chance::Error::<Test>::InvalidOracleSignature
+		);
+		assert_ok!(Admin::pause(Origin::root(), Operation::Settle));
+		assert_noop!(
+			Prediction::resolve_callback(Origin::signed(SETTLER), 0, winning.clone()),
+			pallet_chance::Error::<Test>::SettlementPaused
+		);
+		assert_ok!(Admin::unpause(Origin::root(), Operation::Settle));
+		assert_ok!(Prediction::resolve_callback(Origin::signed(SETTLER), 0, winning.clone()));
+
+		// 600 lost, 60 of it is the protocol fee and 540 is split 3:1
+		assert_eq!(Tokens::free_balance(DOT, &1), INITIAL - 300 + 705);
+		assert_eq!(Tokens::free_balance(DOT, &2), INITIAL - 100 + 235);
+		assert_eq!(Tokens::free_balance(DOT, &3), INITIAL - 600);
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 60);
+		assert_eq!(Tokens::free_balance(DOT, &Prediction::account_id()), 0);
+		assert_eq!(Prediction::result(0), Some(0));
+		assert_eq!(Prediction::resolution_seed(0), Some((winning, closing_hash(0))));
+		assert!(Prediction::pending_resolutions().is_empty());
+		assert!(Prediction::stakers(0).is_empty());
+	}
+)}
+
+#[test]
+fn test_stakes_are_refunded_without_winners() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Prediction::create_round(Origin::root(), DOT, 3, 5));
+		assert_ok!(Prediction::create_round(Origin::root(), DOT, 2, 5));
+		assert_ok!(Prediction::stake(Origin::signed(1), 0, 0, 300));
+		assert_ok!(Prediction::stake(Origin::signed(2), 0, 1, 100));
+
+		Prediction::on_initialize(6);
+		// the round nobody staked on is dropped
+		assert_eq!(Prediction::pending_resolutions(), vec![0]);
+		assert!(Prediction::round(1).is_none());
+
+		build_closing_block(0);
+		assert_ok!(Prediction::resolve_callback(Origin::signed(SETTLER), 0, reveal_winning(0, 2)));
+		assert_eq!(Tokens::free_balance(DOT, &1), INITIAL);
+		assert_eq!(Tokens::free_balance(DOT, &2), INITIAL);
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 0);
+	}
+)}
+
+#[test]
+fn test_winnings_are_shared_exactly() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Prediction::create_round(Origin::root(), DOT, 2, 5));
+		assert_ok!(Prediction::stake(Origin::signed(1), 0, 0, 1000000000000));
+		assert_ok!(Prediction::stake(Origin::signed(2), 0, 0, 2000000000000));
+		assert_ok!(Prediction::stake(Origin::signed(3), 0, 1, 3000000000000));
+		Prediction::on_initialize(6);
+		build_closing_block(0);
+		assert_ok!(Prediction::resolve_callback(Origin::signed(SETTLER), 0, reveal_winning(0, 0)));
+
+		// 2700000000000 after the fee is split 1:2 without rounding
+		assert_eq!(Tokens::free_balance(DOT, &1), INITIAL + 900000000000);
+		assert_eq!(Tokens::free_balance(DOT, &2), INITIAL + 1800000000000);
+		assert_eq!(Tokens::free_balance(DOT, &TREASURY), 300000000000);
+	}
+)}
+
+#[test]
+fn test_stakes_count_towards_gaming_limits() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Prediction::create_round(Origin::root(), DOT, 2, 5));
+		let limit = GamingLimit { max_wager: Some(100), max_loss: None };
+		assert_ok!(pallet_chance::Module::<Test>::set_gaming_limit(Origin::signed(1), DOT, LimitPeriod::Day, limit));
+		assert_ok!(Prediction::stake(Origin::signed(1), 0, 0, 60));
+		assert_noop!(Prediction::stake(Origin::signed(1), 0, 1, 50), pallet_chance::Error::<Test>::WagerLimitExceeded);
+		assert_ok!(Prediction::stake(Origin::signed(1), 0, 1, 40));
+
+		assert_ok!(pallet_chance::Module::<Test>::self_exclude(Origin::signed(2), 10));
+		assert_noop!(Prediction::stake(Origin::signed(2), 0, 0, 10), pallet_chance::Error::<Test>::SelfExcluded);
+	}
+)}
@@ -9,11 +9,12 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 use sp_std::prelude::*;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
-	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature,
+	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature, ModuleId,
 	transaction_validity::{TransactionValidity, TransactionSource},
 };
 use sp_runtime::traits::{
-	BlakeTwo256, Block as BlockT, IdentityLookup, Verify, IdentifyAccount, NumberFor, Saturating, SaturatedConversion
+	AccountIdConversion, BlakeTwo256, Block as BlockT, IdentityLookup, Verify, IdentifyAccount, NumberFor, Saturating,
+	SaturatedConversion
 };
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -33,7 +34,7 @@ pub use pallet_balances::Call as BalancesCall;
 pub use sp_runtime::{Permill, Perbill};
 pub use frame_support::{
 	construct_runtime, parameter_types, StorageValue, debug,
-	traits::{KeyOwnerProofSystem, Randomness},
+	traits::{Filter, KeyOwnerProofSystem, Randomness},
 	weights::{
 		Weight, IdentityFee,
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
@@ -43,6 +44,8 @@ pub use pallet_ocw;
 pub use pallet_pooler;
 pub use pallet_chance;
 pub use pallet_admin;
+pub use pallet_lottery;
+pub use pallet_prediction;
 
 /// Import the template pallet.
 // pub use pallet_template;
@@ -64,6 +67,17 @@ pub type AccountIndex = u32;
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Signed version of `Balance`, used by the multi-currency pallets.
+pub type Amount = i128;
+
+/// Identifier of a currency that can be pooled and wagered.
+pub type CurrencyId = u32;
+
+/// The chain's native token.
+pub const NATIVE_CURRENCY_ID: CurrencyId = 0;
+/// A stablecoin-style test asset held in `orml_tokens`.
+pub const TEST_USD_CURRENCY_ID: CurrencyId = 1;
+
 /// Index of a transaction in the chain.
 pub type Index = u32;
 
@@ -101,10 +115,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("node-template"),
 	impl_name: create_runtime_str!("node-template"),
 	authoring_version: 1,
-	spec_version: 1,
+	spec_version: 2,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -137,11 +151,32 @@ parameter_types! {
 	pub const Version: RuntimeVersion = VERSION;
 }
 
+/// Rejects calls whose operation has been paused in pallet_admin.
+pub struct PausedCallFilter;
+impl Filter<Call> for PausedCallFilter {
+	fn filter(call: &Call) -> bool {
+		use pallet_admin::Operation;
+		let operation = match call {
+			Call::Chance(pallet_chance::Call::bet(..)) => Operation::Bet,
+			Call::Chance(pallet_chance::Call::start_strategy(..)) => Operation::Bet,
+			Call::Pooler(pallet_pooler::Call::deposit(..)) => Operation::Deposit,
+			Call::Pooler(pallet_pooler::Call::withdraw(..)) => Operation::Withdraw,
+			Call::OcwControl(pallet_ocw::Call::submit_fair(..)) => Operation::Settle,
+			Call::Lottery(pallet_lottery::Call::buy_tickets(..)) => Operation::Bet,
+			Call::OcwControl(pallet_ocw::Call::submit_draw(..)) => Operation::Settle,
+			Call::Prediction(pallet_prediction::Call::stake(..)) => Operation::Bet,
+			Call::OcwControl(pallet_ocw::Call::submit_resolution(..)) => Operation::Settle,
+			_ => return true,
+		};
+		!Admin::is_paused(operation)
+	}
+}
+
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Trait for Runtime {
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = ();
+	type BaseCallFilter = PausedCallFilter;
 	/// The identifier used to distinguish between accounts.
 	type AccountId = AccountId;
 	/// The aggregated dispatch type that is available for extrinsics.
@@ -267,31 +302,169 @@ impl pallet_sudo::Trait for Runtime {
 	type Call = Call;
 }
 
+impl orml_tokens::Trait for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type Amount = Amount;
+	type CurrencyId = CurrencyId;
+	type OnReceived = ();
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const GetNativeCurrencyId: CurrencyId = NATIVE_CURRENCY_ID;
+	/// The currency pools and bets were in before they were keyed by currency.
+	pub const LegacyCurrencyId: CurrencyId = NATIVE_CURRENCY_ID;
+}
+
+impl orml_currencies::Trait for Runtime {
+	type Event = Event;
+	type MultiCurrency = Tokens;
+	type NativeCurrency = orml_currencies::BasicCurrencyAdapter<Runtime, Balances, Amount, BlockNumber>;
+	type GetNativeCurrencyId = GetNativeCurrencyId;
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub const SystemDecimals: u128  = 100000000000; 
+	pub TreasuryAccount: AccountId = ModuleId(*b"chn/trsy").into_account();
+	pub const TreasuryCut: Perbill = Perbill::from_percent(10);
+	pub const SettlerCut: Perbill = Perbill::from_percent(10);
+	pub const CircuitBreakerWindow: BlockNumber = HOURS;
+	pub const CircuitBreakerThreshold: Perbill = Perbill::from_percent(20);
+	pub const SettlementDeadline: BlockNumber = 10 * MINUTES;
+	pub const BetCancellationWindow: BlockNumber = 3;
+	pub const BetCancellationFee: Perbill = Perbill::from_percent(2);
+	pub const MaxBetHistory: u32 = 50;
+	pub const BetHistoryRetention: BlockNumber = 30 * DAYS;
+	pub const BlocksPerDay: BlockNumber = DAYS;
+	pub const LimitIncreaseCooldown: BlockNumber = DAYS;
+	pub const ReferralCut: Perbill = Perbill::from_percent(10);
+	pub const MaxReferralDepth: u32 = 100;
 }
 
 impl pallet_chance::Trait for Runtime {
     type Event = Event;
-	type Currency = Balances;
+	type Currency = Currencies;
 	type SystemDecimals = SystemDecimals;
+	type TreasuryAccount = TreasuryAccount;
+	type TreasuryCut = TreasuryCut;
+	type SettlerCut = SettlerCut;
+	type CircuitBreakerWindow = CircuitBreakerWindow;
+	type CircuitBreakerThreshold = CircuitBreakerThreshold;
+	type SettlementDeadline = SettlementDeadline;
+	type CancellationWindow = BetCancellationWindow;
+	type CancellationFee = BetCancellationFee;
+	type MaxBetHistory = MaxBetHistory;
+	type BetHistoryRetention = BetHistoryRetention;
+	type BlocksPerDay = BlocksPerDay;
+	type LimitIncreaseCooldown = LimitIncreaseCooldown;
+	type ReferralCut = ReferralCut;
+	type MaxReferralDepth = MaxReferralDepth;
+	type LegacyCurrencyId = LegacyCurrencyId;
+}
+
+parameter_types! {
+	pub const LotteryRoundLength: BlockNumber = HOURS;
+	pub const LotteryCurrency: CurrencyId = NATIVE_CURRENCY_ID;
+	pub const LotteryTicketPrice: Balance = 10_000_000_000_000;
+	pub const LotteryJackpotCut: Perbill = Perbill::from_percent(50);
+	pub LotteryPrizeTiers: Vec<Perbill> = vec![
+		Perbill::from_percent(60),
+		Perbill::from_percent(20),
+		Perbill::from_percent(10),
+	];
+	pub const MaxTicketsPerRound: u32 = 1_000;
+}
+
+impl pallet_lottery::Trait for Runtime {
+	type Event = Event;
+	type RoundLength = LotteryRoundLength;
+	type LotteryCurrency = LotteryCurrency;
+	type TicketPrice = LotteryTicketPrice;
+	type JackpotCut = LotteryJackpotCut;
+	type PrizeTiers = LotteryPrizeTiers;
+	type MaxTicketsPerRound = MaxTicketsPerRound;
+}
+parameter_types! {
+	pub const PredictionProtocolFee: Perbill = Perbill::from_percent(3);
+	pub const MaxPredictionOutcomes: u32 = 16;
+	pub const MaxStakersPerRound: u32 = 1_000;
+}
+
+impl pallet_prediction::Trait for Runtime {
+	type Event = Event;
+	type CreateOrigin = frame_system::EnsureRoot<AccountId>;
+	type ProtocolFee = PredictionProtocolFee;
+	type MaxOutcomes = MaxPredictionOutcomes;
+	type MaxStakersPerRound = MaxStakersPerRound;
 }
 
+/// Moves slashed settler bonds into the LP pool.
+pub struct SlashToPool;
+impl frame_support::traits::OnUnbalanced<pallet_balances::NegativeImbalance<Runtime>> for SlashToPool {
+	fn on_nonzero_unbalanced(amount: pallet_balances::NegativeImbalance<Runtime>) {
+		use frame_support::traits::Currency;
+		Balances::resolve_creating(&Chance::account_id(), amount);
+	}
+}
+
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * MaximumBlockWeight::get();
+	pub const MaxScheduledPerBlock: u32 = 50;
+}
+
+impl pallet_scheduler::Trait for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type WeightInfo = ();
+}
 
 parameter_types! {
 	pub const MaxSettlers: u32  = 10; 
+	pub const MaxRoleMembers: u32 = 5;
+	pub const SettlerRemovalDelay: BlockNumber = DAYS;
+	pub const SettlerBond: Balance = 1_000_000_000_000_000;
+	pub const SettlementEraLength: BlockNumber = DAYS;
+	pub const MaxMissedSettlements: u32 = 100;
+	pub const MissedSettlementSlash: Perbill = Perbill::from_percent(5);
+	pub const MaxAverageSettlementLatency: BlockNumber = 5 * MINUTES;
+	pub const MaxSettlementDisputes: u32 = 3;
+	pub const ParameterChangeDelay: BlockNumber = 2 * DAYS;
 }
 
 impl pallet_admin::Trait for Runtime {
 	type Event = Event;
 	type MaxSettlers = MaxSettlers;
+	type MaxRoleMembers = MaxRoleMembers;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type RemovalDelay = SettlerRemovalDelay;
+	type SettlersChanged = OcwControl;
+	type Currency = Balances;
+	type SettlerBond = SettlerBond;
+	type Slash = SlashToPool;
+	type EraLength = SettlementEraLength;
+	type MaxMissedSettlements = MaxMissedSettlements;
+	type MissedSettlementSlash = MissedSettlementSlash;
+	type MaxAverageLatency = MaxAverageSettlementLatency;
+	type MaxDisputes = MaxSettlementDisputes;
+	type Proposal = Call;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type ParameterChangeDelay = ParameterChangeDelay;
 }
 
 impl pallet_pooler::Trait for Runtime {
 	type Event = Event;
 	type Balance = u64;
 	type AssetId = u32;
-	type Currency = Balances;
+	type Currency = Currencies;
+	type LegacyCurrencyId = LegacyCurrencyId;
 }
 
 pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
@@ -384,9 +557,14 @@ construct_runtime!(
 		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: pallet_transaction_payment::{Module, Storage},
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
-		OcwControl: pallet_ocw::{Module, Call, Event<T>},
-		Pooler: pallet_pooler::{Module, Call, Storage, Event<T>},
-		Chance: pallet_chance::{Module, Call, Storage, Event<T>},
+		Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
+		Tokens: orml_tokens::{Module, Storage, Call, Event<T>, Config<T>},
+		Currencies: orml_currencies::{Module, Call, Event<T>},
+		OcwControl: pallet_ocw::{Module, Call, Storage, Event<T>},
+		Pooler: pallet_pooler::{Module, Call, Storage, Event<T>, Config<T>},
+		Chance: pallet_chance::{Module, Call, Storage, Event<T>, Config<T>},
+		Lottery: pallet_lottery::{Module, Call, Storage, Event<T>},
+		Prediction: pallet_prediction::{Module, Call, Storage, Event<T>},
 		Admin: pallet_admin::{Module, Call, Storage,Config<T>, Event<T>},
 
 		// Include the custom logic from the template pallet in the runtime.
@@ -551,6 +729,50 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_admin_runtime_api::AdminApi<Block, AccountId, BlockNumber> for Runtime {
+		fn settlers() -> Vec<AccountId> {
+			Admin::settlers()
+		}
+
+		fn settler_stats(who: AccountId) -> pallet_admin_runtime_api::SettlerStats<BlockNumber> {
+			Admin::settler_stats(who)
+		}
+	}
+
+	impl pallet_chance_runtime_api::ChanceApi<Block, AccountId, CurrencyId, Balance, BlockNumber, Hash> for Runtime {
+		fn bet_history(who: AccountId) -> Vec<pallet_chance_runtime_api::BetRecord<CurrencyId, Balance, BlockNumber>> {
+			Chance::bet_history(who)
+		}
+
+		fn bet_totals(currency_id: CurrencyId) -> pallet_chance_runtime_api::BetTotals<Balance> {
+			Chance::bet_totals(currency_id)
+		}
+
+		fn gaming_status(who: AccountId, currency_id: CurrencyId) -> pallet_chance_runtime_api::GamingStatus<Balance, BlockNumber> {
+			Chance::gaming_status(&who, currency_id)
+		}
+
+		fn referral_stats(who: AccountId, currency_id: CurrencyId) -> pallet_chance_runtime_api::ReferralStats<AccountId, Balance> {
+			Chance::referral_stats(&who, currency_id)
+		}
+
+		fn verify_bet(bet_id: pallet_chance_runtime_api::BetId) -> Option<pallet_chance_runtime_api::BetVerification<Hash>> {
+			Chance::verify_bet(bet_id)
+		}
+
+		fn seed_due(subject: pallet_chance_runtime_api::Subject, index: u64) -> bool {
+			Chance::seed_due(subject, index)
+		}
+
+		fn settlement_queue() -> pallet_chance_runtime_api::SettlementQueue<BlockNumber> {
+			Chance::settlement_queue()
+		}
+
+		fn pool_status(currency_id: CurrencyId) -> pallet_chance_runtime_api::PoolStatus<Balance> {
+			Pooler::pool_status(currency_id)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(